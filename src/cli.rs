@@ -0,0 +1,404 @@
+use crate::db::{self, FollowResult, FollowedChannel};
+use crate::handlers::archive_channel_images;
+use crate::jobs::{
+    self, DownloadOptions, Job, JobCheckChannel, JobDownloadVideo, JobFollowChannel, JobNotifier,
+    JobOutcome, JobQueue, QueuedJob, parse_sections,
+};
+use crate::platforms::{
+    UrlKind, canonical_youtube_channel_url, channel_id_from_rss_url, validate_url,
+};
+use crate::trigger::estimate_next_check;
+use crate::worker::{ActiveDownloads, Heartbeat, WorkerState};
+use crate::{DbArgs, FollowingArgs, WorkerArgs, check_prerequisites};
+
+#[derive(Debug, clap::Args)]
+pub(crate) struct FollowArgs {
+    /// URL of the channel to follow.
+    url: String,
+
+    #[arg(long, value_parser = ["often", "sometimes", "rarely"])]
+    /// How often to check the channel for new videos.
+    frequency: String,
+
+    #[arg(long)]
+    /// Number of most recent videos of the channel to download right away.
+    /// Defaults to '--default-download-as-of'.
+    download_as_of: Option<u8>,
+
+    #[arg(long)]
+    /// Record the metadata of the channel's videos instead of downloading them.
+    metadata_only: bool,
+
+    #[arg(long, value_parser = jobs::parse_cookies_file)]
+    /// File system path to a Netscape-formatted cookies file used for downloads
+    /// of this channel's videos, taking precedence over '--cookies-file'.
+    channel_cookies_file: Option<String>,
+
+    #[arg(long)]
+    /// Enumerate the channel's videos via its uploads playlist whenever fetching
+    /// its RSS feed fails.
+    playlist_fallback: bool,
+
+    #[arg(long)]
+    /// On each check, enumerate the channel's videos via its uploads playlist in
+    /// addition to its RSS feed, catching videos the feed misses. Considerably
+    /// slower than only fetching the RSS feed.
+    playlist_merge: bool,
+
+    #[arg(long, value_parser = clap::value_parser!(u16).range(144..=4320))]
+    /// Minimum height in pixels (e.g., 720) the channel's videos need to be
+    /// available in to be downloaded. Videos only available in lower resolutions
+    /// are skipped.
+    min_height: Option<u16>,
+
+    #[arg(long, value_parser = jobs::parse_max_age)]
+    /// Maximum age of the channel's videos to download, either in a short form
+    /// (e.g., '30d', '12h') or as an ISO 8601 duration (e.g., 'P1M'). Older videos
+    /// are skipped, both initially and on checks.
+    max_age: Option<chrono::TimeDelta>,
+
+    #[command(flatten)]
+    following: FollowingArgs,
+
+    #[command(flatten)]
+    db: DbArgs,
+
+    #[command(flatten)]
+    worker: WorkerArgs,
+}
+
+#[derive(Debug, clap::Args)]
+pub(crate) struct CheckArgs {
+    /// URL of the followed channel to check. All followed channels are checked
+    /// if omitted.
+    url: Option<String>,
+
+    #[command(flatten)]
+    db: DbArgs,
+
+    #[command(flatten)]
+    worker: WorkerArgs,
+}
+
+#[derive(Debug, clap::Args)]
+pub(crate) struct ListArgs {
+    #[command(flatten)]
+    db: DbArgs,
+}
+
+#[derive(Debug, clap::Args)]
+pub(crate) struct DownloadArgs {
+    /// URL of the video to download.
+    url: String,
+
+    #[arg(long, value_parser = jobs::parse_filename)]
+    /// Name (without extension) to store the downloaded video under instead of
+    /// its title.
+    filename: Option<String>,
+
+    #[arg(long)]
+    /// Comma-separated list of time ranges (e.g., '*1:30-2:45') to download
+    /// instead of the full video.
+    sections: Option<String>,
+
+    #[command(flatten)]
+    db: DbArgs,
+
+    #[command(flatten)]
+    worker: WorkerArgs,
+}
+
+// Everything the subcommands other than `serve` need to execute jobs right away,
+// without an HTTP server or background tasks. Jobs are executed one after the
+// other by the same worker logic the server uses.
+struct Runner {
+    db_pool: sqlx::AnyPool,
+    http_client: reqwest::Client,
+    page_attempts: u8,
+    submit_job: JobQueue,
+    recv_job: tokio::sync::mpsc::Receiver<QueuedJob>,
+    worker_state: WorkerState,
+}
+
+impl Runner {
+    async fn new(db: &DbArgs, worker: WorkerArgs) -> anyhow::Result<Self> {
+        let db_pool = db.init_db().await?;
+        let http_client = worker.http_client()?;
+        let page_attempts = worker.page_attempts;
+        let (submit_job, recv_job) = JobQueue::new(&db_pool, 256);
+        let fetcher = worker.feed_fetcher()?;
+        let worker_state = worker.into_worker_state(
+            &submit_job,
+            &db_pool,
+            fetcher,
+            &ActiveDownloads::default(),
+            &Heartbeat::new(),
+        )?;
+
+        Ok(Self {
+            db_pool,
+            http_client,
+            page_attempts,
+            submit_job,
+            recv_job,
+            worker_state,
+        })
+    }
+
+    // Execute the supplied job along with all jobs it submits in turn, e.g., the
+    // downloads of new videos found by a channel check.
+    async fn run(&mut self, job: Job) -> anyhow::Result<()> {
+        if self.submit_job.send(job).await.is_err() {
+            return Err(anyhow::anyhow!("Job could not be submitted to queue"));
+        }
+        self.worker_state.run_until_idle(&mut self.recv_job).await;
+        Ok(())
+    }
+
+    // Execute the supplied job like `run` and return the outcome it reported.
+    async fn run_with_outcome(&mut self, job: Job) -> anyhow::Result<JobOutcome> {
+        let (notifier, mut recv_outcome) = JobNotifier::channel();
+        self.run(job.with_notifier(notifier)).await?;
+
+        // Jobs that didn't report an outcome by now were postponed, e.g., to retry
+        // a download on the next day, which isn't waited for.
+        Ok(recv_outcome
+            .try_recv()
+            .unwrap_or_else(|_| Err("Job was postponed to a later time".to_string())))
+    }
+}
+
+/// Start following the channel at the supplied URL just like a request to the
+/// `/channels/follow` endpoint does, and carry out its initial downloads before
+/// returning.
+pub(crate) async fn follow(args: FollowArgs) -> anyhow::Result<()> {
+    check_prerequisites(&args.worker, Some(&args.following))?;
+
+    // The uploads playlist only yields video URLs, not the metadata records stored
+    // for channels followed in metadata-only mode.
+    if args.playlist_fallback && args.metadata_only {
+        return Err(anyhow::anyhow!(
+            "'--playlist-fallback' is not supported in metadata-only mode"
+        ));
+    }
+    if args.playlist_merge && args.metadata_only {
+        return Err(anyhow::anyhow!(
+            "'--playlist-merge' is not supported in metadata-only mode"
+        ));
+    }
+
+    let mut runner = Runner::new(&args.db, args.worker).await?;
+
+    let validated_url = validate_url(
+        &runner.http_client,
+        runner.page_attempts,
+        UrlKind::Channel,
+        &args.url,
+    )
+    .await?;
+    let channel_rss = validated_url.rss_url.unwrap_or_default();
+    let Some(channel_id) = channel_id_from_rss_url(&channel_rss) else {
+        return Err(anyhow::anyhow!(
+            "Failed to extract channel ID from RSS feed URL {channel_rss}"
+        ));
+    };
+
+    match db::follow_channel(
+        &runner.db_pool,
+        &FollowedChannel {
+            url: &validated_url.url,
+            channel_id,
+            rss_url: &channel_rss,
+            frequency: &args.frequency,
+            metadata_only: args.metadata_only,
+            cookies_file: args.channel_cookies_file.as_deref(),
+            playlist_fallback: args.playlist_fallback,
+            playlist_merge: args.playlist_merge,
+            min_height: args.min_height,
+            max_age: args.max_age,
+        },
+    )
+    .await?
+    {
+        FollowResult::Followed => {}
+        FollowResult::Resumed => {
+            println!("Resumed following channel {}", validated_url.url);
+            runner.db_pool.close().await;
+            return Ok(());
+        }
+        FollowResult::AlreadyFollowed => {
+            return Err(anyhow::anyhow!(
+                "Channel {} is already being followed",
+                validated_url.url
+            ));
+        }
+    }
+
+    if let Some(images_dir) = args.following.channel_images_dir {
+        archive_channel_images(
+            runner.http_client.clone(),
+            runner.db_pool.clone(),
+            images_dir,
+            channel_id.to_string(),
+            validated_url.avatar_url,
+            validated_url.banner_url,
+        )
+        .await;
+    }
+
+    runner
+        .run(Job::Follow(JobFollowChannel::new(
+            channel_rss.clone(),
+            args.download_as_of
+                .unwrap_or(args.following.default_download_as_of),
+            args.metadata_only,
+            args.channel_cookies_file,
+            args.min_height,
+            args.max_age,
+            None,
+        )))
+        .await?;
+    println!("Started following channel {}", validated_url.url);
+
+    runner.db_pool.close().await;
+    Ok(())
+}
+
+/// Check the followed channel at the supplied URL, or all followed channels if
+/// none was supplied, for new videos and download them before returning. Fails
+/// if any of the checks failed.
+pub(crate) async fn check(args: CheckArgs) -> anyhow::Result<()> {
+    check_prerequisites(&args.worker, None)?;
+    let mut runner = Runner::new(&args.db, args.worker).await?;
+
+    let rss_urls = match args.url {
+        Some(url) => {
+            let Some(channel_url) = canonical_youtube_channel_url(&url) else {
+                return Err(anyhow::anyhow!("Unsupported or invalid channel URL {url}"));
+            };
+
+            // Channels are stored under the URL they were followed by, so a channel
+            // that was followed via its handle can also be checked via its channel ID.
+            let channel_id = channel_url
+                .strip_prefix("https://www.youtube.com/channel/")
+                .map(str::to_string);
+
+            sqlx::query_scalar::<_, String>(&format!(
+                "SELECT feed_url
+                FROM {channels}
+                WHERE ( name = $1 OR channel_id = $2 ) AND deleted_at IS NULL;",
+                channels = db::table("channels")
+            ))
+            .bind(&channel_url)
+            .bind(&channel_id)
+            .fetch_all(&runner.db_pool)
+            .await?
+        }
+        None => {
+            sqlx::query_scalar::<_, String>(&format!(
+                "SELECT feed_url
+                FROM {channels}
+                WHERE deleted_at IS NULL;",
+                channels = db::table("channels")
+            ))
+            .fetch_all(&runner.db_pool)
+            .await?
+        }
+    };
+    if rss_urls.is_empty() {
+        return Err(anyhow::anyhow!("No matching channel is being followed"));
+    }
+
+    let mut failed_checks = 0_usize;
+    for rss_url in rss_urls {
+        match runner
+            .run_with_outcome(Job::Check(JobCheckChannel::new(rss_url)))
+            .await?
+        {
+            Ok(done) => println!("{}", done.status),
+            Err(failure) => {
+                eprintln!("{failure}");
+                failed_checks = failed_checks.saturating_add(1);
+            }
+        }
+    }
+
+    runner.db_pool.close().await;
+    if failed_checks > 0 {
+        return Err(anyhow::anyhow!("{failed_checks} channel check(s) failed"));
+    }
+    Ok(())
+}
+
+/// Print the followed channels, one per line, with their check frequency, the
+/// time of their last check, whether only their metadata is recorded, and the
+/// approximate time of their next check, all separated by tabs.
+pub(crate) async fn list(args: ListArgs) -> anyhow::Result<()> {
+    let db_pool = args.db.init_db().await?;
+
+    let channels = sqlx::query_as::<_, (String, String, Option<String>, i64)>(&format!(
+        "SELECT name, check_frequency, last_checked, metadata_only
+        FROM {channels}
+        WHERE deleted_at IS NULL
+        ORDER BY name ASC;",
+        channels = db::table("channels")
+    ))
+    .fetch_all(&db_pool)
+    .await?;
+
+    for (name, check_frequency, last_checked, metadata_only) in channels {
+        let next_check = last_checked
+            .as_deref()
+            .and_then(|l| estimate_next_check(&check_frequency, l))
+            .map_or_else(|| "unknown".to_string(), |n| n.format("%+").to_string());
+        println!(
+            "{name}\t{check_frequency}\t{}\t{}\t{next_check}",
+            last_checked.as_deref().unwrap_or("never"),
+            if metadata_only != 0 {
+                "metadata-only"
+            } else {
+                "download"
+            },
+        );
+    }
+
+    db_pool.close().await;
+    Ok(())
+}
+
+/// Download the video at the supplied URL just like a request to the
+/// `/downloads/ondemand` endpoint does, returning once the download finished.
+pub(crate) async fn download(args: DownloadArgs) -> anyhow::Result<()> {
+    check_prerequisites(&args.worker, None)?;
+    let options = DownloadOptions {
+        filename: args.filename,
+        sections: args.sections.as_deref().map(parse_sections).transpose()?,
+        ..DownloadOptions::default()
+    };
+    let mut runner = Runner::new(&args.db, args.worker).await?;
+
+    let validated_url = validate_url(
+        &runner.http_client,
+        runner.page_attempts,
+        UrlKind::Video,
+        &args.url,
+    )
+    .await?
+    .url;
+
+    let outcome = runner
+        .run_with_outcome(Job::Download(JobDownloadVideo::with_options(
+            validated_url,
+            options,
+        )))
+        .await?;
+
+    runner.db_pool.close().await;
+    match outcome {
+        Ok(done) => {
+            println!("{}", done.status);
+            Ok(())
+        }
+        Err(failure) => Err(anyhow::anyhow!(failure)),
+    }
+}
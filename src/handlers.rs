@@ -1,21 +1,143 @@
-use crate::jobs::{Job, JobDownloadVideo, JobFollowChannel};
+use crate::db::{self, Backfill, FollowResult, FollowedChannel, QueueEntry};
+use crate::jobs::{
+    DownloadOptions, DownloadOrigin, Job, JobBackfillChannel, JobDownloadVideo, JobFollowChannel,
+    JobNotifier, JobQueue, parse_audio_format, parse_audio_lang, parse_audio_quality,
+    parse_callback_url, parse_channel_cookies_file, parse_filename, parse_format_sort,
+    parse_max_age, parse_max_retries, parse_min_height, parse_sections,
+};
+use crate::platforms::{
+    UrlKind, canonical_youtube_channel_url, channel_id_from_rss_url, validate_url,
+};
+use crate::rss::FeedFetcher;
+use crate::trigger::estimate_next_check;
+use crate::worker::{
+    ActiveDownloads, Heartbeat, path_within_dir, probe_dependencies, probe_writable, ytdlp_version,
+};
+use axum::response::IntoResponse;
 use tracing::{Level, event};
 
+// Maximum number of channels a single request to `POST /channels/follow/bulk` may
+// ask to follow, as each one is validated against the platform in turn.
+const BULK_FOLLOW_MAX_CHANNELS: usize = 100;
+
+// Number of seconds for which `GET /healthz` reuses the results of its slower
+// checks, which call external executables and write to the file system, so that
+// frequent probing stays cheap.
+const HEALTH_CACHE_SECS: u64 = 30;
+
+// Number of seconds after which `GET /healthz` considers the database unreachable.
+const HEALTH_DB_TIMEOUT_SECS: u64 = 5;
+
 #[derive(Debug, serde::Deserialize)]
 pub(crate) struct DownloadsOnDemandReq {
     url: String,
+    audio_lang: Option<String>,
+    extract_audio: Option<String>,
+    audio_quality: Option<String>,
+    filename: Option<String>,
+    sections: Option<String>,
+    format_sort: Option<String>,
+    max_retries: Option<u8>,
+    wait_secs: Option<u16>,
+    #[serde(default)]
+    return_path: bool,
+    callback_url: Option<String>,
 }
 
 #[derive(Debug, serde::Serialize)]
 pub(crate) struct DownloadsOnDemandResp {
     status: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    path: Option<String>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub(crate) struct DownloadsStatusReq {
+    url: Option<String>,
+}
+
+#[derive(Debug, serde::Serialize, sqlx::FromRow)]
+pub(crate) struct DownloadRecord {
+    url: String,
+    file_name: String,
+    sha256: Option<String>,
+    completed_at: String,
+    ytdlp_version: Option<String>,
+    postprocessing_error: Option<String>,
+    #[sqlx(skip)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    path: Option<String>,
+}
+
+#[derive(Debug, serde::Serialize, sqlx::FromRow)]
+pub(crate) struct FailedDownloadRecord {
+    url: String,
+    state: String,
+    reason: String,
+    failed_at: String,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub(crate) struct DownloadsStatusResp {
+    status: String,
+    downloads: Vec<DownloadRecord>,
+    failed: Vec<FailedDownloadRecord>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub(crate) struct StatsFailuresReq {
+    hours: Option<u32>,
+}
+
+#[derive(Debug, serde::Serialize, sqlx::FromRow)]
+pub(crate) struct FailureReasonCount {
+    state: String,
+    count: i64,
+    #[sqlx(skip)]
+    percent: f64,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub(crate) struct StatsFailuresResp {
+    status: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    since: Option<String>,
+    total: i64,
+    reasons: Vec<FailureReasonCount>,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub(crate) struct ActiveDownloadInfo {
+    url: String,
+    attempt: u8,
+    started_at: String,
+    elapsed_secs: i64,
+    progress_percent: Option<f64>,
+    speed_bytes_per_sec: Option<f64>,
+    eta_secs: Option<u64>,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub(crate) struct DownloadsActiveResp {
+    status: String,
+    downloads: Vec<ActiveDownloadInfo>,
 }
 
 #[derive(Debug, serde::Deserialize)]
 pub(crate) struct ChannelFollowReq {
     url: String,
-    download_as_of: u8,
+    download_as_of: Option<u8>,
     frequency: String,
+    #[serde(default)]
+    metadata_only: bool,
+    cookies_file: Option<String>,
+    #[serde(default)]
+    playlist_fallback: bool,
+    #[serde(default)]
+    playlist_merge: bool,
+    min_height: Option<u16>,
+    max_age: Option<String>,
+    first_check_after_mins: Option<u16>,
 }
 
 #[derive(Debug, serde::Serialize)]
@@ -23,179 +145,390 @@ pub(crate) struct ChannelFollowResp {
     status: String,
 }
 
-#[derive(Debug, Clone)]
-enum YouTubeURL {
-    Video,
-    Channel,
+#[derive(Clone, Copy, Debug, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+/// Outcome of a request to follow a channel.
+pub(crate) enum FollowOutcome {
+    /// The channel is followed from now on and its initial downloads are queued.
+    Followed,
+    /// The channel was unfollowed before and is followed again.
+    Resumed,
+    /// The channel is already being followed.
+    Duplicate,
+    /// The request or the channel's URL is invalid.
+    Invalid,
+    /// Following the channel failed on autotube's side.
+    Failed,
 }
 
-impl std::fmt::Display for YouTubeURL {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
+impl FollowOutcome {
+    // Return the status code responding to a single request to follow a channel
+    // with this outcome.
+    fn status_code(self) -> axum::http::StatusCode {
         match self {
-            YouTubeURL::Video => write!(f, "video"),
-            YouTubeURL::Channel => write!(f, "channel"),
+            FollowOutcome::Followed => axum::http::StatusCode::CREATED,
+            FollowOutcome::Resumed => axum::http::StatusCode::OK,
+            FollowOutcome::Duplicate | FollowOutcome::Invalid => {
+                axum::http::StatusCode::BAD_REQUEST
+            }
+            FollowOutcome::Failed => axum::http::StatusCode::INTERNAL_SERVER_ERROR,
         }
     }
 }
 
-#[derive(Clone, Debug)]
-/// Wraps state that each HTTP handler might need to have access to.
-pub(crate) struct HTTPHandlerState {
-    submit_job: tokio::sync::mpsc::Sender<Job>,
-    db_pool: sqlx::sqlite::SqlitePool,
+#[derive(Debug, serde::Serialize)]
+pub(crate) struct ChannelFollowBulkResult {
+    url: String,
+    result: FollowOutcome,
+    status: String,
 }
 
-impl HTTPHandlerState {
-    pub(crate) fn new(
-        submit_job: &tokio::sync::mpsc::Sender<Job>,
-        db_pool: &sqlx::sqlite::SqlitePool,
-    ) -> Self {
-        HTTPHandlerState {
-            submit_job: submit_job.clone(),
-            db_pool: db_pool.clone(),
-        }
-    }
+#[derive(Debug, serde::Serialize)]
+pub(crate) struct ChannelFollowBulkResp {
+    status: String,
+    results: Vec<ChannelFollowBulkResult>,
 }
 
-// Verifies that everthing after 'youtube.com/watch?' in a `YouTube` video URL
-// is as required, meaning that we need to find the video ID in the query
-// parameters. Only used as part of validate_youtube_url, which means that we
-// don't check for 'youtube.com/watch?' at the front of the URL string again.
-// Returns the final, validated, full `YouTube` URL to the video.
-fn validate_youtube_video_url(url: &str) -> anyhow::Result<String> {
-    let url_parts = &url[18..].split('&').collect::<Vec<&str>>();
+#[derive(Debug, serde::Deserialize)]
+pub(crate) struct ChannelUnfollowReq {
+    url: String,
+}
 
-    let Some(video_id) = url_parts
-        .iter()
-        .find(|&&p| p.len() == 13 && p.starts_with("v="))
-    else {
-        event!(
-            Level::DEBUG,
-            "Video ID parameter missing from or incorrect in YouTube URL: {url}"
-        );
-        return Err(anyhow::anyhow!(
-            "Video ID parameter missing from or incorrect in YouTube URL"
-        ));
-    };
+#[derive(Debug, serde::Deserialize)]
+pub(crate) struct ChannelUnfollowParams {
+    #[serde(default)]
+    hard: bool,
+}
 
-    Ok(format!("https://www.youtube.com/watch?{video_id}"))
+#[derive(Debug, serde::Serialize)]
+pub(crate) struct ChannelUnfollowResp {
+    status: String,
 }
 
-// Verifies that the submitted `YouTube` channel URL indeed links to an existing
-// channel by first cleaning the URL and then making an HTTP GET request to see
-// if we get a 200 OK response. If successful, extracts the RSS feed URL
-// embedded on the YouTube channel webpage. Returns the final, validated, full
-// `YouTube` URL to the channel and the extracted RSS feed URL.
-async fn validate_youtube_channel_url(url: &str) -> anyhow::Result<(String, String)> {
-    let (base_part, channel_part) = url.split_at(13);
-    let channel_name = match channel_part.split_once('/') {
-        Some((name, _)) => name,
-        None => channel_part,
-    };
+#[derive(Debug, serde::Deserialize)]
+pub(crate) struct ChannelNameReq {
+    channel: String,
+    name: String,
+}
 
-    let channel_url = format!("https://www.{base_part}{channel_name}").to_lowercase();
+#[derive(Debug, serde::Serialize)]
+pub(crate) struct ChannelNameResp {
+    status: String,
+}
 
-    let Ok(resp) = reqwest::get(&channel_url).await else {
-        event!(
-            Level::DEBUG,
-            "Failed to connect to supplied YouTube channel URL via HTTP: {channel_url}"
-        );
-        return Err(anyhow::anyhow!(
-            "Failed to connect to supplied YouTube channel URL via HTTP"
-        ));
-    };
+#[derive(Debug, serde::Deserialize)]
+pub(crate) struct ChannelsReq {
+    frequency: Option<String>,
+    q: Option<String>,
+    platform: Option<String>,
+}
 
-    if resp.status() != reqwest::StatusCode::OK {
-        event!(
-            Level::DEBUG,
-            "Supplied YouTube channel URL did not return 200 OK: {channel_url}"
-        );
-        return Err(anyhow::anyhow!(
-            "Supplied YouTube channel URL did not return 200 OK"
-        ));
-    }
+#[derive(Debug, serde::Serialize)]
+pub(crate) struct ChannelInfo {
+    name: String,
+    feed_url: String,
+    check_frequency: String,
+    metadata_only: bool,
+    last_checked: Option<String>,
+    next_check_estimate: Option<String>,
+}
 
-    let Ok(channel_webpage) = resp.text().await else {
-        event!(
-            Level::DEBUG,
-            "Unable to obtain webpage content for supplied YouTube channel URL: {channel_url}"
-        );
-        return Err(anyhow::anyhow!(
-            "Unable to obtain webpage content for supplied YouTube channel URL"
-        ));
-    };
+#[derive(Debug, serde::Serialize)]
+pub(crate) struct ChannelsResp {
+    status: String,
+    channels: Vec<ChannelInfo>,
+}
 
-    // Find the byte position within the webpage text that signifies the start of
-    // the canonical link element which contains the YouTube ID URL of the channel.
-    // Manual tests have shown that this item is present in the DOM of any YouTube
-    // channel webpage.
-    let Some(rss_url_offset) =
-        channel_webpage.find("<link rel=\"alternate\" type=\"application/rss+xml\" title=\"RSS\" href=\"https://www.youtube.com/feeds/videos.xml?channel_id=UC")
-    else {
-        event!(
-            Level::DEBUG,
-            "Didn't find channel ID in YouTube channel webpage: {channel_url}"
-        );
-        return Err(anyhow::anyhow!(
-            "Didn't find channel ID in YouTube channel webpage"
-        ));
-    };
+#[derive(Debug, serde::Serialize)]
+pub(crate) struct QueueResp {
+    status: String,
+    jobs: Vec<QueueEntry>,
+}
 
-    // Extract channel ID from webpage string by extracting the right 24 characters
-    // from within the webpage text.
-    let rss_url = channel_webpage[(rss_url_offset + 67)..(rss_url_offset + 143)].to_string();
+#[derive(Debug, serde::Serialize)]
+pub(crate) struct ChannelsRefreshFeedsResp {
+    status: String,
+    updated: usize,
+    unchanged: usize,
+    failed: usize,
+}
 
-    Ok((channel_url, rss_url))
+#[derive(Debug, serde::Deserialize)]
+pub(crate) struct ChannelRecordsReq {
+    channel: Option<String>,
 }
 
-// Verifies that the supplied URL is a valid YouTube URL (either pointing to a
-// video or a channel) and rejects all others. If successful, returns the
-// cleaned and canonicalized version of the input URL.
-async fn validate_youtube_url(kind: YouTubeURL, url: &str) -> anyhow::Result<(String, String)> {
-    if url.is_empty() {
-        return Err(anyhow::anyhow!(format!("Empty YouTube {kind} URL")));
-    }
+#[derive(Debug, serde::Serialize, sqlx::FromRow)]
+pub(crate) struct ChannelRecord {
+    url: String,
+    channel: String,
+    title: String,
+    published: String,
+    description: String,
+    recorded_at: String,
+}
 
-    let url = url.trim_start_matches("https://");
-    let url = url.trim_start_matches("http://");
-    let url = url.trim_start_matches("www.");
+#[derive(Debug, serde::Serialize)]
+pub(crate) struct ChannelRecordsResp {
+    status: String,
+    records: Vec<ChannelRecord>,
+}
 
-    match kind {
-        YouTubeURL::Video => {
-            if url.starts_with("youtube.com/watch?") {
-                let valid_url = validate_youtube_video_url(url)?;
-                Ok((valid_url, String::new()))
-            } else {
-                event!(Level::DEBUG, "Unsupported or invalid video URL: {url}");
-                Err(anyhow::anyhow!("Unsupported or invalid video URL"))
-            }
+#[derive(Debug, serde::Deserialize)]
+pub(crate) struct ChannelFeedReq {
+    url: String,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub(crate) struct ChannelFeedResp {
+    status: String,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub(crate) struct ChannelBackfillReq {
+    url: String,
+    max_videos: Option<u32>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub(crate) struct ChannelRetryFailedReq {
+    url: String,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub(crate) struct ChannelRetryFailedResp {
+    status: String,
+    requeued: usize,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub(crate) struct ChannelBackfillResp {
+    status: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    id: Option<String>,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub(crate) struct ChannelsBackfillsResp {
+    status: String,
+    backfills: Vec<Backfill>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub(crate) struct ValidateReq {
+    url: String,
+    kind: UrlKind,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub(crate) struct ValidateResp {
+    status: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rss_url: Option<String>,
+}
+
+#[derive(Clone, Debug, serde::Serialize)]
+/// Health of a single component autotube depends on. Unhealthy critical
+/// components make autotube as a whole unhealthy.
+pub(crate) struct ComponentHealth {
+    healthy: bool,
+    critical: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    version: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+impl ComponentHealth {
+    // Report a component as healthy, along with its version, if known.
+    fn healthy(critical: bool, version: Option<String>) -> Self {
+        Self {
+            healthy: true,
+            critical,
+            version,
+            error: None,
         }
-        YouTubeURL::Channel => {
-            if url.starts_with("youtube.com/@") {
-                let (valid_url, channel_id) = validate_youtube_channel_url(url).await?;
-                Ok((valid_url, channel_id))
-            } else {
-                event!(Level::DEBUG, "Unsupported or invalid channel URL: {url}");
-                Err(anyhow::anyhow!("Unsupported or invalid channel URL"))
-            }
+    }
+
+    // Report a component as unhealthy for the supplied reason.
+    fn unhealthy(critical: bool, error: String) -> Self {
+        Self {
+            healthy: false,
+            critical,
+            version: None,
+            error: Some(error),
+        }
+    }
+}
+
+#[derive(Clone, Debug, serde::Serialize)]
+pub(crate) struct HealthComponents {
+    database: ComponentHealth,
+    worker: ComponentHealth,
+    #[serde(flatten)]
+    slow: SlowHealthChecks,
+}
+
+#[derive(Clone, Debug, serde::Serialize)]
+// Results of the health checks that are too slow to run on each request to
+// `GET /healthz`, so they are cached for `HEALTH_CACHE_SECS`.
+struct SlowHealthChecks {
+    yt_dlp: ComponentHealth,
+    ffmpeg: ComponentHealth,
+    tmp_dir: ComponentHealth,
+    video_dir: ComponentHealth,
+}
+
+// Cache of the most recent results of the slower health checks along with the
+// time they were taken at.
+type HealthCache =
+    std::sync::Arc<tokio::sync::Mutex<Option<(std::time::Instant, SlowHealthChecks)>>>;
+
+#[derive(Debug, serde::Serialize)]
+pub(crate) struct HealthSummaryResp {
+    healthy: bool,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub(crate) struct HealthResp {
+    status: String,
+    healthy: bool,
+    queued_jobs: usize,
+    secs_since_worker_activity: i64,
+    components: HealthComponents,
+}
+
+#[derive(Clone, Debug)]
+/// Wraps state that each HTTP handler might need to have access to.
+pub(crate) struct HTTPHandlerState {
+    submit_job: JobQueue,
+    db_pool: sqlx::AnyPool,
+    read_pool: sqlx::AnyPool,
+    http_client: reqwest::Client,
+    fetcher: FeedFetcher,
+    active_downloads: ActiveDownloads,
+    heartbeat: Heartbeat,
+    stall_secs: i64,
+    default_download_as_of: u8,
+    channel_images_dir: Option<String>,
+    cookies_dir: Option<String>,
+    page_attempts: u8,
+    video_dir: String,
+    tmp_dir: String,
+    backfill_max_videos: u32,
+    health_cache: HealthCache,
+}
+
+impl HTTPHandlerState {
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn new(
+        submit_job: &JobQueue,
+        db_pool: &sqlx::AnyPool,
+        read_pool: &sqlx::AnyPool,
+        http_client: reqwest::Client,
+        fetcher: &FeedFetcher,
+        active_downloads: &ActiveDownloads,
+        heartbeat: &Heartbeat,
+        stall_secs: u32,
+        default_download_as_of: u8,
+        channel_images_dir: Option<String>,
+        cookies_dir: Option<String>,
+        page_attempts: u8,
+        video_dir: String,
+        tmp_dir: String,
+        backfill_max_videos: u32,
+    ) -> Self {
+        HTTPHandlerState {
+            submit_job: submit_job.clone(),
+            db_pool: db_pool.clone(),
+            read_pool: read_pool.clone(),
+            http_client,
+            fetcher: fetcher.clone(),
+            active_downloads: std::sync::Arc::clone(active_downloads),
+            heartbeat: heartbeat.clone(),
+            stall_secs: i64::from(stall_secs),
+            default_download_as_of,
+            channel_images_dir,
+            cookies_dir,
+            page_attempts,
+            video_dir,
+            tmp_dir,
+            backfill_max_videos,
+            health_cache: HealthCache::default(),
         }
     }
 }
 
+// Validates the optional per-download overrides of the global configuration
+// supplied in an on-demand download request and collects them for the job.
+fn parse_download_options(payload: &DownloadsOnDemandReq) -> anyhow::Result<DownloadOptions> {
+    Ok(DownloadOptions {
+        audio_lang: payload
+            .audio_lang
+            .as_deref()
+            .map(parse_audio_lang)
+            .transpose()?,
+        extract_audio: payload
+            .extract_audio
+            .as_deref()
+            .map(parse_audio_format)
+            .transpose()?,
+        audio_quality: payload
+            .audio_quality
+            .as_deref()
+            .map(parse_audio_quality)
+            .transpose()?,
+        filename: payload
+            .filename
+            .as_deref()
+            .map(parse_filename)
+            .transpose()?,
+        sections: payload
+            .sections
+            .as_deref()
+            .map(parse_sections)
+            .transpose()?,
+        format_sort: payload
+            .format_sort
+            .as_deref()
+            .map(parse_format_sort)
+            .transpose()?,
+        max_retries: payload.max_retries.map(parse_max_retries).transpose()?,
+        callback_url: payload
+            .callback_url
+            .as_deref()
+            .map(parse_callback_url)
+            .transpose()?,
+        ..DownloadOptions::default()
+    })
+}
+
 /// Handle a POST request with a JSON payload containing a video URL to download
-/// in the background. Currently, the only supported video platform to download
-/// from is `YouTube`, any other domain is rejected as part of input validation.
+/// in the background. The URL is validated by the validator of the platform
+/// registered for the URL's domain, any other domain is rejected as part of
+/// input validation. Currently, the only registered platform is `YouTube`.
+#[allow(clippy::too_many_lines)]
 pub(crate) async fn post_downloads_ondemand(
     axum::extract::State(state): axum::extract::State<HTTPHandlerState>,
     axum::Json(payload): axum::Json<DownloadsOnDemandReq>,
 ) -> (axum::http::StatusCode, axum::Json<DownloadsOnDemandResp>) {
-    let (validated_url, _) = match validate_youtube_url(YouTubeURL::Video, &payload.url).await {
-        Ok(u) => u,
+    let validated_url = match validate_url(
+        &state.http_client,
+        state.page_attempts,
+        UrlKind::Video,
+        &payload.url,
+    )
+    .await
+    {
+        Ok(v) => v.url,
         Err(e) => {
             return (
                 axum::http::StatusCode::BAD_REQUEST,
                 axum::Json(DownloadsOnDemandResp {
                     status: e.to_string(),
+                    path: None,
                 }),
             );
         }
@@ -205,11 +538,40 @@ pub(crate) async fn post_downloads_ondemand(
         "Received valid video URL to download: {validated_url}"
     );
 
+    // Validate the optional per-download overrides of the global configuration.
+    let options = match parse_download_options(&payload) {
+        Ok(o) => o,
+        Err(e) => {
+            return (
+                axum::http::StatusCode::BAD_REQUEST,
+                axum::Json(DownloadsOnDemandResp {
+                    status: e.to_string(),
+                    path: None,
+                }),
+            );
+        }
+    };
+
+    // If the client wants to wait for the download to finish, have the worker
+    // report the outcome of the download back.
+    let (notifier, recv_outcome) = if payload.wait_secs.is_some() {
+        let (notifier, recv_outcome) = JobNotifier::channel();
+        (notifier, Some(recv_outcome))
+    } else {
+        (JobNotifier::default(), None)
+    };
+
     // Submit validated URL via channel to a queue from which workers take URLs to
     // go and download them as videos.
     if (state
         .submit_job
-        .send(Job::Download(JobDownloadVideo::new(validated_url.clone())))
+        .send(
+            Job::Download(JobDownloadVideo::with_options(
+                validated_url.clone(),
+                options,
+            ))
+            .with_notifier(notifier),
+        )
         .await)
         .is_err()
     {
@@ -221,6 +583,7 @@ pub(crate) async fn post_downloads_ondemand(
             axum::http::StatusCode::INTERNAL_SERVER_ERROR,
             axum::Json(DownloadsOnDemandResp {
                 status: "Video could not be submitted to download queue".to_string(),
+                path: None,
             }),
         );
     }
@@ -229,97 +592,725 @@ pub(crate) async fn post_downloads_ondemand(
         "Sent video URL to background process for downloading"
     );
 
+    if let (Some(wait_secs), Some(recv_outcome)) = (payload.wait_secs, recv_outcome) {
+        let (status_code, status, path) = match tokio::time::timeout(
+            std::time::Duration::from_secs(u64::from(wait_secs)),
+            recv_outcome,
+        )
+        .await
+        {
+            Ok(Ok(Ok(done))) => {
+                // Only hand out the final path of the video if requested and if it
+                // lies within the video directory.
+                let path = done
+                    .path
+                    .filter(|_| payload.return_path)
+                    .and_then(|p| path_within_dir(std::path::Path::new(&state.video_dir), &p))
+                    .map(|p| p.to_string_lossy().into_owned());
+                (axum::http::StatusCode::OK, done.status, path)
+            }
+            Ok(Ok(Err(failure))) => (axum::http::StatusCode::INTERNAL_SERVER_ERROR, failure, None),
+            Ok(Err(_)) => (
+                axum::http::StatusCode::INTERNAL_SERVER_ERROR,
+                "Download ended without reporting an outcome".to_string(),
+                None,
+            ),
+            Err(_) => (
+                axum::http::StatusCode::ACCEPTED,
+                format!("Video still downloading after {wait_secs} seconds"),
+                None,
+            ),
+        };
+        return (
+            status_code,
+            axum::Json(DownloadsOnDemandResp { status, path }),
+        );
+    }
+
     (
         axum::http::StatusCode::CREATED,
         axum::Json(DownloadsOnDemandResp {
             status: "Video submitted to download queue".to_string(),
+            path: None,
         }),
     )
 }
 
-#[allow(clippy::too_many_lines)]
-pub(crate) async fn post_channels_follow(
+/// Handle a GET request listing the records of completed downloads, most recent
+/// first, including the checksums of the downloaded files if they were computed,
+/// followed by the records of downloads that failed for good along with the state
+/// they ended in (e.g., `geo_blocked`). If the optional `url` query parameter
+/// contains a video URL, only the records of downloads of that video are listed.
+pub(crate) async fn get_downloads_status(
     axum::extract::State(state): axum::extract::State<HTTPHandlerState>,
-    axum::Json(payload): axum::Json<ChannelFollowReq>,
-) -> (axum::http::StatusCode, axum::Json<ChannelFollowResp>) {
-    let frequency = match payload.frequency.as_str() {
-        "often" => "often",
-        "sometimes" => "sometimes",
-        "rarely" => "rarely",
-        &_ => {
+    axum::extract::Query(params): axum::extract::Query<DownloadsStatusReq>,
+) -> (axum::http::StatusCode, axum::Json<DownloadsStatusResp>) {
+    let validated_url = match params.url {
+        Some(url) => {
+            match validate_url(
+                &state.http_client,
+                state.page_attempts,
+                UrlKind::Video,
+                &url,
+            )
+            .await
+            {
+                Ok(v) => Some(v.url),
+                Err(e) => {
+                    return (
+                        axum::http::StatusCode::BAD_REQUEST,
+                        axum::Json(DownloadsStatusResp {
+                            status: e.to_string(),
+                            downloads: Vec::new(),
+                            failed: Vec::new(),
+                        }),
+                    );
+                }
+            }
+        }
+        None => None,
+    };
+
+    // Without a video URL, the condition on the URL holds for all records.
+    let downloads = sqlx::query_as::<_, DownloadRecord>(&format!(
+        "SELECT url, file_name, sha256, completed_at, ytdlp_version, postprocessing_error
+        FROM {downloads}
+        WHERE $1 = '' OR url = $1
+        ORDER BY completed_at DESC;",
+        downloads = db::table("downloads")
+    ))
+    .bind(validated_url.clone().unwrap_or_default())
+    .fetch_all(&state.read_pool)
+    .await;
+    let failed = sqlx::query_as::<_, FailedDownloadRecord>(&format!(
+        "SELECT url, state, reason, failed_at
+        FROM {failed_downloads}
+        WHERE $1 = '' OR url = $1
+        ORDER BY failed_at DESC;",
+        failed_downloads = db::table("failed_downloads")
+    ))
+    .bind(validated_url.unwrap_or_default())
+    .fetch_all(&state.read_pool)
+    .await;
+
+    match (downloads, failed) {
+        (Ok(mut downloads), Ok(failed)) => {
+            // Add the final path of each downloaded video that still lies within the
+            // video directory.
+            let video_dir = std::path::Path::new(&state.video_dir);
+            for download in &mut downloads {
+                download.path = path_within_dir(video_dir, &video_dir.join(&download.file_name))
+                    .map(|p| p.to_string_lossy().into_owned());
+            }
+            (
+                axum::http::StatusCode::OK,
+                axum::Json(DownloadsStatusResp {
+                    status: format!(
+                        "Found {} completed and {} failed downloads",
+                        downloads.len(),
+                        failed.len()
+                    ),
+                    downloads,
+                    failed,
+                }),
+            )
+        }
+        (Err(e), _) | (_, Err(e)) => {
+            event!(
+                Level::WARN,
+                "Retrieving downloads from database failed: {e}"
+            );
+            (
+                axum::http::StatusCode::INTERNAL_SERVER_ERROR,
+                axum::Json(DownloadsStatusResp {
+                    status: "Retrieving downloads from database failed".to_string(),
+                    downloads: Vec::new(),
+                    failed: Vec::new(),
+                }),
+            )
+        }
+    }
+}
+
+/// Handle a GET request breaking down the downloads that failed for good by the
+/// state they ended in (e.g., `geo_blocked`), most frequent first, along with
+/// each state's share of all failures. If the optional `hours` query parameter
+/// is supplied, only downloads that failed within that many past hours count.
+pub(crate) async fn get_stats_failures(
+    axum::extract::State(state): axum::extract::State<HTTPHandlerState>,
+    axum::extract::Query(params): axum::extract::Query<StatsFailuresReq>,
+) -> (axum::http::StatusCode, axum::Json<StatsFailuresResp>) {
+    let since = match params.hours {
+        Some(0) => {
             return (
                 axum::http::StatusCode::BAD_REQUEST,
-                axum::Json(ChannelFollowResp {
-                    status: "Field 'argument' needs to be one of: 'often', 'sometimes', 'rarely'"
-                        .to_string(),
+                axum::Json(StatsFailuresResp {
+                    status: "Parameter 'hours' needs to be at least 1".to_string(),
+                    since: None,
+                    total: 0,
+                    reasons: Vec::new(),
                 }),
             );
         }
+        Some(hours) => chrono::Utc::now()
+            .checked_sub_signed(chrono::TimeDelta::hours(i64::from(hours)))
+            .map(|at| at.fixed_offset().format("%+").to_string()),
+        None => None,
     };
 
-    let (validated_url, channel_rss) =
-        match validate_youtube_url(YouTubeURL::Channel, &payload.url).await {
-            Ok(u) => u,
-            Err(e) => {
-                return (
-                    axum::http::StatusCode::BAD_REQUEST,
-                    axum::Json(ChannelFollowResp {
-                        status: e.to_string(),
-                    }),
-                );
-            }
-        };
-    event!(
-        Level::DEBUG,
-        "Received valid channel URL to follow: {validated_url}"
-    );
+    // Failure timestamps are all stored in UTC and the same format, so comparing
+    // them as strings orders them in time. Without a time window, the condition
+    // holds for all records.
+    let reasons = sqlx::query_as::<_, FailureReasonCount>(&format!(
+        "SELECT state, COUNT(*) AS count
+        FROM {failed_downloads}
+        WHERE $1 = '' OR failed_at >= $1
+        GROUP BY state
+        ORDER BY count DESC, state ASC;",
+        failed_downloads = db::table("failed_downloads")
+    ))
+    .bind(since.clone().unwrap_or_default())
+    .fetch_all(&state.read_pool)
+    .await;
 
-    // Enter YouTube channel with metadata into table tracking channels.
-    match sqlx::query!(
-        "INSERT INTO channels ( name, platform, feed_url, check_frequency )
-        VALUES ( $1, $2, $3, $4 );",
-        validated_url,
-        "youtube",
-        channel_rss,
-        frequency,
-    )
-    .execute(&state.db_pool)
-    .await
-    {
-        Ok(_) => {}
-        Err(e) => match e {
-            sqlx::Error::Database(err_db) if err_db.is_unique_violation() => {
-                event!(
-                    Level::DEBUG,
-                    "Submitted channel is already being followed: {validated_url}"
-                );
-                return (
-                    axum::http::StatusCode::BAD_REQUEST,
-                    axum::Json(ChannelFollowResp {
-                        status: "Submitted channel is already being followed".to_string(),
-                    }),
-                );
+    match reasons {
+        Ok(mut reasons) => {
+            let total = reasons.iter().map(|r| r.count).sum::<i64>();
+            #[allow(clippy::cast_precision_loss)]
+            for reason in &mut reasons {
+                reason.percent = (reason.count as f64) * 100.0 / (total as f64);
             }
-            _ => {
-                event!(
-                    Level::WARN,
-                    "Inserting new channel to follow into database failed: {e}"
-                );
-                return (
-                    axum::http::StatusCode::INTERNAL_SERVER_ERROR,
-                    axum::Json(ChannelFollowResp {
-                        status: "Inserting new channel to follow into database failed".to_string(),
-                    }),
-                );
+            (
+                axum::http::StatusCode::OK,
+                axum::Json(StatsFailuresResp {
+                    status: format!("Found {total} failed downloads in {} states", reasons.len()),
+                    since,
+                    total,
+                    reasons,
+                }),
+            )
+        }
+        Err(e) => {
+            event!(
+                Level::WARN,
+                "Retrieving failed downloads from database failed: {e}"
+            );
+            (
+                axum::http::StatusCode::INTERNAL_SERVER_ERROR,
+                axum::Json(StatsFailuresResp {
+                    status: "Retrieving failed downloads from database failed".to_string(),
+                    since,
+                    total: 0,
+                    reasons: Vec::new(),
+                }),
+            )
+        }
+    }
+}
+
+/// Handle a GET request listing the downloads currently in progress, longest
+/// running first, with the time elapsed since they started and their progress,
+/// if already reported by `yt-dlp`.
+pub(crate) async fn get_downloads_active(
+    axum::extract::State(state): axum::extract::State<HTTPHandlerState>,
+) -> (axum::http::StatusCode, axum::Json<DownloadsActiveResp>) {
+    let Ok(active) = state.active_downloads.lock() else {
+        event!(
+            Level::WARN,
+            "Lock on registry of active downloads is poisoned"
+        );
+        return (
+            axum::http::StatusCode::INTERNAL_SERVER_ERROR,
+            axum::Json(DownloadsActiveResp {
+                status: "Retrieving active downloads failed".to_string(),
+                downloads: Vec::new(),
+            }),
+        );
+    };
+
+    let now = chrono::Utc::now();
+    let mut downloads = active
+        .values()
+        .map(|d| ActiveDownloadInfo {
+            url: d.url.clone(),
+            attempt: d.attempt,
+            started_at: d.started_at.fixed_offset().format("%+").to_string(),
+            elapsed_secs: now.signed_duration_since(d.started_at).num_seconds(),
+            progress_percent: d.progress_percent,
+            speed_bytes_per_sec: d.speed_bytes_per_sec,
+            eta_secs: d.eta_secs,
+        })
+        .collect::<Vec<ActiveDownloadInfo>>();
+    drop(active);
+    downloads.sort_by_key(|d| std::cmp::Reverse(d.elapsed_secs));
+
+    (
+        axum::http::StatusCode::OK,
+        axum::Json(DownloadsActiveResp {
+            status: format!("Found {} active downloads", downloads.len()),
+            downloads,
+        }),
+    )
+}
+
+// Run the health checks that call external executables and write to the file
+// system: whether the temporary and video directories are writable, and whether
+// 'yt-dlp' and 'ffmpeg' can be called. Executables are called from within the
+// temporary directory, falling back to the current directory if that one is
+// unusable. Without 'ffmpeg', some downloads still succeed, so it isn't critical.
+fn probe_slow_health(tmp_dir: &str, video_dir: &str) -> SlowHealthChecks {
+    let dir_health = |dir: &str| match probe_writable(dir) {
+        Ok(()) => ComponentHealth::healthy(true, None),
+        Err(e) => ComponentHealth::unhealthy(true, e),
+    };
+    let tmp_dir_health = dir_health(tmp_dir);
+
+    let probed = probe_dependencies(if tmp_dir_health.healthy { tmp_dir } else { "." });
+    let found = |name: &str| {
+        probed
+            .iter()
+            .any(|(n, version)| *n == name && version.is_some())
+    };
+    let ffmpeg_version = probed
+        .iter()
+        .find(|(name, _)| *name == "ffmpeg")
+        .and_then(|(_, version)| version.clone())
+        .filter(|version| !version.is_empty());
+
+    SlowHealthChecks {
+        yt_dlp: if found("yt-dlp") {
+            ComponentHealth::healthy(true, ytdlp_version(&probed))
+        } else {
+            ComponentHealth::unhealthy(true, "No 'yt-dlp' executable found".to_string())
+        },
+        ffmpeg: if found("ffmpeg") {
+            ComponentHealth::healthy(false, ffmpeg_version)
+        } else {
+            ComponentHealth::unhealthy(false, "No 'ffmpeg' executable found".to_string())
+        },
+        tmp_dir: tmp_dir_health,
+        video_dir: dir_health(video_dir),
+    }
+}
+
+// Return the results of the slower health checks, running them anew only if the
+// cached ones are older than `HEALTH_CACHE_SECS`. Concurrent requests wait for a
+// single run of the checks.
+async fn slow_health(state: &HTTPHandlerState) -> SlowHealthChecks {
+    let mut cache = state.health_cache.lock().await;
+    if let Some((checked_at, checks)) = cache.as_ref()
+        && checked_at.elapsed() < std::time::Duration::from_secs(HEALTH_CACHE_SECS)
+    {
+        return checks.clone();
+    }
+
+    let (tmp_dir, video_dir) = (state.tmp_dir.clone(), state.video_dir.clone());
+    let checks =
+        match tokio::task::spawn_blocking(move || probe_slow_health(&tmp_dir, &video_dir)).await {
+            Ok(checks) => checks,
+            Err(e) => {
+                let error = format!("Health check failed to run: {e}");
+                return SlowHealthChecks {
+                    yt_dlp: ComponentHealth::unhealthy(true, error.clone()),
+                    ffmpeg: ComponentHealth::unhealthy(false, error.clone()),
+                    tmp_dir: ComponentHealth::unhealthy(true, error.clone()),
+                    video_dir: ComponentHealth::unhealthy(true, error),
+                };
             }
+        };
+    *cache = Some((std::time::Instant::now(), checks.clone()));
+    checks
+}
+
+// Check the health of autotube and the components it depends on: the database,
+// the worker, 'yt-dlp', 'ffmpeg', and the temporary and video directories.
+// Reports unhealthy if any critical component is, e.g., if jobs are waiting in
+// the queue but the worker has not taken any job off the queue within the
+// configured window, or if the worker is gone altogether. 'ffmpeg' missing is
+// reported, but isn't critical.
+async fn check_health(state: &HTTPHandlerState) -> (axum::http::StatusCode, HealthResp) {
+    let queued_jobs = state.submit_job.len();
+    let secs_since_worker_activity = state.heartbeat.secs_since_last_beat();
+
+    let worker = if state.submit_job.is_closed() {
+        ComponentHealth::unhealthy(true, "Worker is not running".to_string())
+    } else if queued_jobs > 0 && secs_since_worker_activity > state.stall_secs {
+        ComponentHealth::unhealthy(
+            true,
+            format!(
+                "Worker stalled with {queued_jobs} jobs queued, no activity for {secs_since_worker_activity} seconds"
+            ),
+        )
+    } else {
+        ComponentHealth::healthy(true, None)
+    };
+
+    let database = match tokio::time::timeout(
+        std::time::Duration::from_secs(HEALTH_DB_TIMEOUT_SECS),
+        sqlx::query("SELECT 1;").execute(&state.db_pool),
+    )
+    .await
+    {
+        Ok(Ok(_)) => ComponentHealth::healthy(true, None),
+        Ok(Err(e)) => {
+            event!(Level::WARN, "Health check of database failed: {e}");
+            ComponentHealth::unhealthy(true, "Database unreachable".to_string())
+        }
+        Err(_) => ComponentHealth::unhealthy(
+            true,
+            format!("Database unreachable within {HEALTH_DB_TIMEOUT_SECS} seconds"),
+        ),
+    };
+
+    let components = HealthComponents {
+        database,
+        worker,
+        slow: slow_health(state).await,
+    };
+    let problems = [
+        &components.database,
+        &components.worker,
+        &components.slow.yt_dlp,
+        &components.slow.ffmpeg,
+        &components.slow.tmp_dir,
+        &components.slow.video_dir,
+    ]
+    .into_iter()
+    .filter(|c| c.critical && !c.healthy)
+    .filter_map(|c| c.error.clone())
+    .collect::<Vec<String>>();
+
+    let (status_code, status) = if problems.is_empty() {
+        (axum::http::StatusCode::OK, "Healthy".to_string())
+    } else {
+        let status = problems.join("; ");
+        event!(Level::WARN, "Health check failed: {status}");
+        (axum::http::StatusCode::SERVICE_UNAVAILABLE, status)
+    };
+
+    (
+        status_code,
+        HealthResp {
+            status,
+            healthy: problems.is_empty(),
+            queued_jobs,
+            secs_since_worker_activity,
+            components,
+        },
+    )
+}
+
+/// Handle a GET request for whether autotube is healthy, which doesn't require
+/// the bearer token so that liveness probes can do without it. Only reports
+/// whether all critical components are healthy, via the status code and
+/// `healthy`, leaving the details to `GET /healthz/details`.
+pub(crate) async fn get_healthz(
+    axum::extract::State(state): axum::extract::State<HTTPHandlerState>,
+) -> (axum::http::StatusCode, axum::Json<HealthSummaryResp>) {
+    let (status_code, health) = check_health(&state).await;
+    (
+        status_code,
+        axum::Json(HealthSummaryResp {
+            healthy: health.healthy,
+        }),
+    )
+}
+
+/// Handle a GET request for the health of autotube and each component it depends
+/// on, including the problems found and the versions of 'yt-dlp' and 'ffmpeg'.
+pub(crate) async fn get_healthz_details(
+    axum::extract::State(state): axum::extract::State<HTTPHandlerState>,
+) -> (axum::http::StatusCode, axum::Json<HealthResp>) {
+    let (status_code, health) = check_health(&state).await;
+    (status_code, axum::Json(health))
+}
+
+/// Handle a POST request with a JSON payload containing a video or channel URL
+/// that is only validated and canonicalized, exactly as it would be when
+/// submitted to the other endpoints. Nothing is written to the database and no
+/// job is submitted, which makes this endpoint suitable for validating input in
+/// clients before submitting it.
+pub(crate) async fn post_validate(
+    axum::extract::State(state): axum::extract::State<HTTPHandlerState>,
+    axum::Json(payload): axum::Json<ValidateReq>,
+) -> (axum::http::StatusCode, axum::Json<ValidateResp>) {
+    let kind = payload.kind.clone();
+    match validate_url(
+        &state.http_client,
+        state.page_attempts,
+        payload.kind,
+        &payload.url,
+    )
+    .await
+    {
+        Ok(validated) => (
+            axum::http::StatusCode::OK,
+            axum::Json(ValidateResp {
+                status: format!("Valid {kind} URL"),
+                url: Some(validated.url),
+                rss_url: validated.rss_url,
+            }),
+        ),
+        Err(e) => (
+            axum::http::StatusCode::BAD_REQUEST,
+            axum::Json(ValidateResp {
+                status: e.to_string(),
+                url: None,
+                rss_url: None,
+            }),
+        ),
+    }
+}
+
+/// Handle a POST request with a JSON payload containing the URL of a channel to
+/// follow, how often to check it, and optional settings to follow it with.
+pub(crate) async fn post_channels_follow(
+    axum::extract::State(state): axum::extract::State<HTTPHandlerState>,
+    axum::Json(payload): axum::Json<ChannelFollowReq>,
+) -> (axum::http::StatusCode, axum::Json<ChannelFollowResp>) {
+    let (outcome, status) = follow_channel(&state, payload).await;
+    (
+        outcome.status_code(),
+        axum::Json(ChannelFollowResp { status }),
+    )
+}
+
+/// Handle a POST request with a JSON array of payloads as accepted by `POST
+/// /channels/follow`, following each of the channels in turn. Responds with the
+/// outcome for each channel in the order they were supplied. If all channels
+/// share the same outcome, the status code is the one of a single request to
+/// follow a channel with that outcome, otherwise it is `207 Multi-Status`.
+pub(crate) async fn post_channels_follow_bulk(
+    axum::extract::State(state): axum::extract::State<HTTPHandlerState>,
+    axum::Json(payload): axum::Json<Vec<ChannelFollowReq>>,
+) -> (axum::http::StatusCode, axum::Json<ChannelFollowBulkResp>) {
+    if payload.is_empty() || payload.len() > BULK_FOLLOW_MAX_CHANNELS {
+        return (
+            axum::http::StatusCode::BAD_REQUEST,
+            axum::Json(ChannelFollowBulkResp {
+                status: format!(
+                    "Payload needs to contain between 1 and {BULK_FOLLOW_MAX_CHANNELS} channels"
+                ),
+                results: Vec::new(),
+            }),
+        );
+    }
+
+    let mut results = Vec::with_capacity(payload.len());
+    for req in payload {
+        let url = req.url.clone();
+        let (result, status) = follow_channel(&state, req).await;
+        results.push(ChannelFollowBulkResult {
+            url,
+            result,
+            status,
+        });
+    }
+
+    let num_followed = results
+        .iter()
+        .filter(|r| matches!(r.result, FollowOutcome::Followed | FollowOutcome::Resumed))
+        .count();
+    let status_code = results
+        .iter()
+        .map(|r| r.result.status_code())
+        .reduce(|a, b| {
+            if a == b {
+                a
+            } else {
+                axum::http::StatusCode::MULTI_STATUS
+            }
+        })
+        .unwrap_or(axum::http::StatusCode::BAD_REQUEST);
+
+    (
+        status_code,
+        axum::Json(ChannelFollowBulkResp {
+            status: format!("Followed {num_followed} of {} channels", results.len()),
+            results,
+        }),
+    )
+}
+
+// Validate the supplied request to follow a channel, start following the channel,
+// and queue its initial downloads, as requested via `POST /channels/follow` or as
+// part of `POST /channels/follow/bulk`. Returns the outcome along with a message
+// describing it.
+#[allow(clippy::too_many_lines)]
+async fn follow_channel(
+    state: &HTTPHandlerState,
+    payload: ChannelFollowReq,
+) -> (FollowOutcome, String) {
+    let frequency = match payload.frequency.as_str() {
+        "often" => "often",
+        "sometimes" => "sometimes",
+        "rarely" => "rarely",
+        &_ => {
+            return (
+                FollowOutcome::Invalid,
+                "Field 'argument' needs to be one of: 'often', 'sometimes', 'rarely'".to_string(),
+            );
+        }
+    };
+
+    // The uploads playlist only yields video URLs, not the metadata records stored
+    // for channels followed in metadata-only mode.
+    if payload.playlist_fallback && payload.metadata_only {
+        return (
+            FollowOutcome::Invalid,
+            "Field 'playlist_fallback' is not supported in metadata-only mode".to_string(),
+        );
+    }
+    if payload.playlist_merge && payload.metadata_only {
+        return (
+            FollowOutcome::Invalid,
+            "Field 'playlist_merge' is not supported in metadata-only mode".to_string(),
+        );
+    }
+    if payload.first_check_after_mins == Some(0) {
+        return (
+            FollowOutcome::Invalid,
+            "Field 'first_check_after_mins' needs to be at least 1".to_string(),
+        );
+    }
+    if let Some(Err(e)) = payload.min_height.map(parse_min_height) {
+        return (
+            FollowOutcome::Invalid,
+            format!("Field 'min_height' is invalid: {e}"),
+        );
+    }
+
+    let max_age = match payload.max_age.as_deref().map(parse_max_age).transpose() {
+        Ok(m) => m,
+        Err(e) => {
+            return (
+                FollowOutcome::Invalid,
+                format!("Field 'max_age' is invalid: {e}"),
+            );
+        }
+    };
+
+    let cookies_file = match payload
+        .cookies_file
+        .as_deref()
+        .map(|c| parse_channel_cookies_file(state.cookies_dir.as_deref(), c))
+    {
+        Some(Ok(c)) => Some(c),
+        Some(Err(e)) => {
+            return (
+                FollowOutcome::Invalid,
+                format!("Field 'cookies_file' is invalid: {e}"),
+            );
+        }
+        None => None,
+    };
+
+    let (validated_url, channel_rss, avatar_url, banner_url) = match validate_url(
+        &state.http_client,
+        state.page_attempts,
+        UrlKind::Channel,
+        &payload.url,
+    )
+    .await
+    {
+        Ok(v) => (
+            v.url,
+            v.rss_url.unwrap_or_default(),
+            v.avatar_url,
+            v.banner_url,
+        ),
+        Err(e) => {
+            return (FollowOutcome::Invalid, e.to_string());
+        }
+    };
+    event!(
+        Level::DEBUG,
+        "Received valid channel URL to follow: {validated_url}"
+    );
+
+    let Some(channel_id) = channel_id_from_rss_url(&channel_rss) else {
+        event!(
+            Level::WARN,
+            "Failed to extract channel ID from RSS feed URL: {channel_rss}"
+        );
+        return (
+            FollowOutcome::Failed,
+            "Failed to extract channel ID from RSS feed URL".to_string(),
+        );
+    };
+
+    // Enter YouTube channel with metadata into table tracking channels. If the
+    // channel was unfollowed before, its history is retained, so no initial
+    // downloads take place.
+    match db::follow_channel(
+        &state.db_pool,
+        &FollowedChannel {
+            url: &validated_url,
+            channel_id,
+            rss_url: &channel_rss,
+            frequency,
+            metadata_only: payload.metadata_only,
+            cookies_file: cookies_file.as_deref(),
+            playlist_fallback: payload.playlist_fallback,
+            playlist_merge: payload.playlist_merge,
+            min_height: payload.min_height,
+            max_age,
         },
+    )
+    .await
+    {
+        Ok(FollowResult::Followed) => {}
+        Ok(FollowResult::Resumed) => {
+            event!(
+                Level::INFO,
+                "Resumed following previously unfollowed channel {validated_url}"
+            );
+            return (
+                FollowOutcome::Resumed,
+                format!("Resumed following channel {validated_url}"),
+            );
+        }
+        Ok(FollowResult::AlreadyFollowed) => {
+            event!(
+                Level::DEBUG,
+                "Submitted channel is already being followed: {validated_url}"
+            );
+            return (
+                FollowOutcome::Duplicate,
+                "Submitted channel is already being followed".to_string(),
+            );
+        }
+        Err(e) => {
+            event!(Level::WARN, "{e:#}");
+            return (FollowOutcome::Failed, e.to_string());
+        }
+    }
+
+    // If configured, archive the channel's images in the background, as they
+    // aren't needed for following the channel.
+    if let Some(images_dir) = state.channel_images_dir.clone() {
+        tokio::spawn(archive_channel_images(
+            state.http_client.clone(),
+            state.db_pool.clone(),
+            images_dir,
+            channel_id.to_string(),
+            avatar_url,
+            banner_url,
+        ));
     }
 
     if (state
         .submit_job
         .send(Job::Follow(JobFollowChannel::new(
             channel_rss.clone(),
-            payload.download_as_of,
+            payload
+                .download_as_of
+                .unwrap_or(state.default_download_as_of),
+            payload.metadata_only,
+            cookies_file,
+            payload.min_height,
+            max_age,
+            payload.first_check_after_mins,
         )))
         .await)
         .is_err()
@@ -329,10 +1320,8 @@ pub(crate) async fn post_channels_follow(
             "Initial download of new channel could not be sent to queue: {validated_url}"
         );
         return (
-            axum::http::StatusCode::INTERNAL_SERVER_ERROR,
-            axum::Json(ChannelFollowResp {
-                status: "Initial download of new channel could not be sent to queue".to_string(),
-            }),
+            FollowOutcome::Failed,
+            "Initial download of new channel could not be sent to queue".to_string(),
         );
     }
     event!(
@@ -341,103 +1330,831 @@ pub(crate) async fn post_channels_follow(
     );
 
     (
-        axum::http::StatusCode::CREATED,
-        axum::Json(ChannelFollowResp {
-            status: format!("Started following channel {validated_url}"),
-        }),
+        FollowOutcome::Followed,
+        format!("Started following channel {validated_url}"),
     )
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+// Download the image found at `url` into `images_dir` under a name made up of
+// the channel ID and the kind of image (e.g., 'avatar'), with the extension
+// derived from the image's content type. Returns the path of the stored image,
+// or `None` if any step failed.
+async fn download_channel_image(
+    http_client: &reqwest::Client,
+    url: &str,
+    images_dir: &str,
+    channel_id: &str,
+    kind: &str,
+) -> Option<String> {
+    let res = async {
+        let resp = http_client.get(url).send().await?.error_for_status()?;
+        let extension = match resp
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|t| t.to_str().ok())
+        {
+            Some("image/png") => "png",
+            Some("image/webp") => "webp",
+            Some("image/gif") => "gif",
+            _ => "jpg",
+        };
+        let image = resp.bytes().await?;
 
-    #[tokio::test]
-    async fn test_validate_video_urls() {
-        // Below URL inputs to validate_youtube_url() should all produce an Error
-        // result with the associated error message.
-        let should_error = [
-            ("", "Empty YouTube video URL"),
-            ("abc", "Unsupported or invalid video URL"),
-            ("http://vimeo.com", "Unsupported or invalid video URL"),
-            ("https://www.google.com", "Unsupported or invalid video URL"),
-            (
-                "youtube.org/watch?v=0123456789a",
-                "Unsupported or invalid video URL",
-            ),
+        tokio::fs::create_dir_all(images_dir).await?;
+        let path =
+            std::path::Path::new(images_dir).join(format!("{channel_id}_{kind}.{extension}"));
+        tokio::fs::write(&path, &image).await?;
+        anyhow::Ok(path)
+    }
+    .await;
+
+    match res {
+        Ok(path) => Some(path.to_string_lossy().into_owned()),
+        Err(e) => {
+            event!(
+                Level::WARN,
+                "Failed to archive {kind} image of channel {channel_id}: {e}"
+            );
+            None
+        }
+    }
+}
+
+// Archive the avatar and banner images of the channel with the supplied ID in
+// `images_dir` and store the paths of the archived images with the channel.
+// Images that weren't found or failed to download are stored as NULL.
+pub(crate) async fn archive_channel_images(
+    http_client: reqwest::Client,
+    db_pool: sqlx::AnyPool,
+    images_dir: String,
+    channel_id: String,
+    avatar_url: Option<String>,
+    banner_url: Option<String>,
+) {
+    let avatar_path = match avatar_url {
+        Some(url) => {
+            download_channel_image(&http_client, &url, &images_dir, &channel_id, "avatar").await
+        }
+        None => None,
+    };
+    let banner_path = match banner_url {
+        Some(url) => {
+            download_channel_image(&http_client, &url, &images_dir, &channel_id, "banner").await
+        }
+        None => None,
+    };
+
+    if let Err(e) = sqlx::query(&format!(
+        "UPDATE {channels}
+        SET avatar_path = $1, banner_path = $2
+        WHERE channel_id = $3;",
+        channels = db::table("channels")
+    ))
+    .bind(&avatar_path)
+    .bind(&banner_path)
+    .bind(&channel_id)
+    .execute(&db_pool)
+    .await
+    {
+        event!(
+            Level::WARN,
+            "Storing paths of archived images of channel {channel_id} failed: {e}"
+        );
+    }
+}
+
+/// Handle a POST request with a JSON payload containing the URL of a channel to
+/// stop following. By default, the channel is only marked as unfollowed, which
+/// excludes it from checks but retains its history, so that following it again
+/// picks up where it left off. If the `hard` query parameter is set to `true`,
+/// the channel and the metadata records of its videos are deleted instead.
+pub(crate) async fn post_channels_unfollow(
+    axum::extract::State(state): axum::extract::State<HTTPHandlerState>,
+    axum::extract::Query(params): axum::extract::Query<ChannelUnfollowParams>,
+    axum::Json(payload): axum::Json<ChannelUnfollowReq>,
+) -> (axum::http::StatusCode, axum::Json<ChannelUnfollowResp>) {
+    let Some(channel_url) = canonical_youtube_channel_url(&payload.url) else {
+        return (
+            axum::http::StatusCode::BAD_REQUEST,
+            axum::Json(ChannelUnfollowResp {
+                status: "Unsupported or invalid channel URL".to_string(),
+            }),
+        );
+    };
+
+    // Channels are stored under the URL they were followed by, so a channel that
+    // was followed via its handle can also be unfollowed via its channel ID.
+    let channel_id = channel_url
+        .strip_prefix("https://www.youtube.com/channel/")
+        .map(str::to_string);
+
+    let res = if params.hard {
+        match sqlx::query(&format!(
+            "DELETE FROM {video_records}
+            WHERE feed_url IN ( SELECT feed_url FROM {channels} WHERE name = $1 OR channel_id = $2 );",
+            video_records = db::table("video_records"),
+            channels = db::table("channels")
+        ))
+        .bind(&channel_url)
+        .bind(&channel_id)
+        .execute(&state.db_pool)
+        .await
+        {
+            Ok(_) => {
+                sqlx::query(&format!(
+                    "DELETE FROM {channels}
+                    WHERE name = $1 OR channel_id = $2;",
+                    channels = db::table("channels")
+                ))
+                .bind(&channel_url)
+                .bind(&channel_id)
+                .execute(&state.db_pool)
+                .await
+            }
+            Err(e) => Err(e),
+        }
+    } else {
+        let now_str = chrono::Utc::now().fixed_offset().format("%+").to_string();
+        sqlx::query(&format!(
+            "UPDATE {channels}
+            SET deleted_at = $1
+            WHERE ( name = $2 OR channel_id = $3 ) AND deleted_at IS NULL;",
+            channels = db::table("channels")
+        ))
+        .bind(&now_str)
+        .bind(&channel_url)
+        .bind(&channel_id)
+        .execute(&state.db_pool)
+        .await
+    };
+
+    match res {
+        Ok(res) if res.rows_affected() == 0 => (
+            axum::http::StatusCode::NOT_FOUND,
+            axum::Json(ChannelUnfollowResp {
+                status: "Submitted channel is not being followed".to_string(),
+            }),
+        ),
+        Ok(_) => {
+            event!(
+                Level::INFO,
+                "Unfollowed channel {channel_url} (hard delete: {})",
+                params.hard
+            );
             (
-                "https://www.youtube.com/watch?v=0123456789",
-                "Video ID parameter missing from or incorrect in YouTube URL",
-            ),
+                axum::http::StatusCode::OK,
+                axum::Json(ChannelUnfollowResp {
+                    status: format!("Stopped following channel {channel_url}"),
+                }),
+            )
+        }
+        Err(e) => {
+            event!(Level::WARN, "Unfollowing channel in database failed: {e}");
             (
-                "https://www.youtube.com/watch?v=0123456789ab",
-                "Video ID parameter missing from or incorrect in YouTube URL",
-            ),
+                axum::http::StatusCode::INTERNAL_SERVER_ERROR,
+                axum::Json(ChannelUnfollowResp {
+                    status: "Unfollowing channel in database failed".to_string(),
+                }),
+            )
+        }
+    }
+}
+
+/// Handle a PATCH request with a JSON payload containing a followed channel,
+/// referred to by its RSS feed URL or its current name, and the new name to store
+/// for it. Names are unique across channels, so a new name that is already taken
+/// by another channel is rejected.
+pub(crate) async fn patch_channels_name(
+    axum::extract::State(state): axum::extract::State<HTTPHandlerState>,
+    axum::Json(payload): axum::Json<ChannelNameReq>,
+) -> (axum::http::StatusCode, axum::Json<ChannelNameResp>) {
+    let name = payload.name.trim();
+    if name.is_empty() || name.chars().any(char::is_control) {
+        return (
+            axum::http::StatusCode::BAD_REQUEST,
+            axum::Json(ChannelNameResp {
+                status: "Field 'name' must neither be empty nor contain control characters"
+                    .to_string(),
+            }),
+        );
+    }
+
+    match sqlx::query(&format!(
+        "UPDATE {channels}
+        SET name = $1
+        WHERE feed_url = $2 OR name = $2;",
+        channels = db::table("channels")
+    ))
+    .bind(name)
+    .bind(&payload.channel)
+    .execute(&state.db_pool)
+    .await
+    {
+        Ok(res) if res.rows_affected() == 0 => (
+            axum::http::StatusCode::NOT_FOUND,
+            axum::Json(ChannelNameResp {
+                status: "Submitted channel is not known".to_string(),
+            }),
+        ),
+        Ok(_) => {
+            event!(
+                Level::INFO,
+                "Renamed channel {} to '{name}'",
+                payload.channel
+            );
             (
-                "https://www.youtube.com/watch?k=0123456789a",
-                "Video ID parameter missing from or incorrect in YouTube URL",
-            ),
+                axum::http::StatusCode::OK,
+                axum::Json(ChannelNameResp {
+                    status: format!("Renamed channel to '{name}'"),
+                }),
+            )
+        }
+        Err(sqlx::Error::Database(err_db)) if err_db.is_unique_violation() => (
+            axum::http::StatusCode::CONFLICT,
+            axum::Json(ChannelNameResp {
+                status: format!("Name '{name}' is already taken by another channel"),
+            }),
+        ),
+        Err(e) => {
+            event!(Level::WARN, "Renaming channel in database failed: {e}");
             (
-                "https://www.youtube.com/watch?v=0123456789&list=abcdefghijklmnopqrstuvwxyzeRgBdnBM",
-                "Video ID parameter missing from or incorrect in YouTube URL",
-            ),
-        ];
-
-        for (url, exp_err) in &should_error {
-            assert!(
-                validate_youtube_url(YouTubeURL::Video, url)
-                    .await
-                    .is_err_and(|e| {
-                        let ret_err = e.to_string();
-                        if ret_err == *exp_err {
-                            true
-                        } else {
-                            println!("{url}: Expected '{exp_err}', but got '{ret_err}'");
-                            false
-                        }
-                    })
-            );
+                axum::http::StatusCode::INTERNAL_SERVER_ERROR,
+                axum::Json(ChannelNameResp {
+                    status: "Renaming channel in database failed".to_string(),
+                }),
+            )
         }
+    }
+}
 
-        // Below URL inputs to validate_youtube_url() should all produce an Ok result
-        // with the associated valid URL returned.
-        let should_succeed = [
-            (
-                "youtube.com/watch?v=0123456789a",
-                "https://www.youtube.com/watch?v=0123456789a",
-            ),
+/// Handle a GET request listing the followed channels by name, along with their
+/// check frequency, the time of their last check, and an estimate of the time of
+/// their next check. As checks are spread across each interval and randomly
+/// jittered, the estimate is only approximate. Channels that haven't been checked
+/// yet lack both. The optional query parameters `frequency`, `q` (a substring of
+/// the name, matched case-insensitively), and `platform` narrow the list down.
+pub(crate) async fn get_channels(
+    axum::extract::State(state): axum::extract::State<HTTPHandlerState>,
+    axum::extract::Query(params): axum::extract::Query<ChannelsReq>,
+) -> (axum::http::StatusCode, axum::Json<ChannelsResp>) {
+    if let Some(frequency) = &params.frequency
+        && !matches!(frequency.as_str(), "often" | "sometimes" | "rarely")
+    {
+        return (
+            axum::http::StatusCode::BAD_REQUEST,
+            axum::Json(ChannelsResp {
+                status: "Parameter 'frequency' needs to be one of: 'often', 'sometimes', 'rarely'"
+                    .to_string(),
+                channels: Vec::new(),
+            }),
+        );
+    }
+
+    let filter = db::ChannelFilter {
+        frequency: params.frequency,
+        name_contains: params.q.filter(|q| !q.is_empty()),
+        platform: params.platform,
+    };
+    match db::list_channels(&state.read_pool, &filter).await {
+        Ok(channels) => {
+            let channels = channels
+                .into_iter()
+                .map(|c| {
+                    let next_check_estimate = c
+                        .last_checked
+                        .as_deref()
+                        .and_then(|l| estimate_next_check(&c.check_frequency, l))
+                        .map(|n| n.format("%+").to_string());
+                    ChannelInfo {
+                        name: c.name,
+                        feed_url: c.feed_url,
+                        check_frequency: c.check_frequency,
+                        metadata_only: c.metadata_only != 0,
+                        last_checked: c.last_checked,
+                        next_check_estimate,
+                    }
+                })
+                .collect::<Vec<ChannelInfo>>();
             (
-                "www.youtube.com/watch?v=0123456789a",
-                "https://www.youtube.com/watch?v=0123456789a",
-            ),
+                axum::http::StatusCode::OK,
+                axum::Json(ChannelsResp {
+                    status: format!("Following {} channels", channels.len()),
+                    channels,
+                }),
+            )
+        }
+        Err(e) => {
+            event!(Level::WARN, "Listing followed channels failed: {e}");
             (
-                "http://youtube.com/watch?v=0123456789a",
-                "https://www.youtube.com/watch?v=0123456789a",
+                axum::http::StatusCode::INTERNAL_SERVER_ERROR,
+                axum::Json(ChannelsResp {
+                    status: "Listing followed channels failed".to_string(),
+                    channels: Vec::new(),
+                }),
+            )
+        }
+    }
+}
+
+/// Handle a POST request to validate each followed channel anew and update its
+/// stored RSS feed URL and channel ID if they changed, e.g., because `YouTube`
+/// changed its feed URL scheme. Channels are validated one after the other via
+/// the URL they were followed by, or via their channel ID if they were renamed
+/// since. Responds with the numbers of updated, unchanged, and failed channels.
+pub(crate) async fn post_channels_refresh_feeds(
+    axum::extract::State(state): axum::extract::State<HTTPHandlerState>,
+) -> (axum::http::StatusCode, axum::Json<ChannelsRefreshFeedsResp>) {
+    let channels = match sqlx::query_as::<_, (String, String, String)>(&format!(
+        "SELECT name, channel_id, feed_url
+        FROM {channels}
+        WHERE deleted_at IS NULL;",
+        channels = db::table("channels")
+    ))
+    .fetch_all(&state.db_pool)
+    .await
+    {
+        Ok(c) => c,
+        Err(e) => {
+            event!(Level::WARN, "Listing followed channels failed: {e}");
+            return (
+                axum::http::StatusCode::INTERNAL_SERVER_ERROR,
+                axum::Json(ChannelsRefreshFeedsResp {
+                    status: "Listing followed channels failed".to_string(),
+                    updated: 0,
+                    unchanged: 0,
+                    failed: 0,
+                }),
+            );
+        }
+    };
+
+    let (mut updated, mut unchanged, mut failed) = (0_usize, 0_usize, 0_usize);
+    for (name, channel_id, feed_url) in channels {
+        let channel_url = canonical_youtube_channel_url(&name)
+            .unwrap_or_else(|| format!("https://www.youtube.com/channel/{channel_id}"));
+
+        let rss_url = match validate_url(
+            &state.http_client,
+            state.page_attempts,
+            UrlKind::Channel,
+            &channel_url,
+        )
+        .await
+        {
+            Ok(v) => v.rss_url.unwrap_or_default(),
+            Err(e) => {
+                event!(
+                    Level::WARN,
+                    "Validating channel {name} to refresh its RSS feed URL failed: {e}"
+                );
+                failed = failed.saturating_add(1);
+                continue;
+            }
+        };
+        let Some(new_channel_id) = channel_id_from_rss_url(&rss_url) else {
+            event!(
+                Level::WARN,
+                "Failed to extract channel ID from RSS feed URL {rss_url} of channel {name}"
+            );
+            failed = failed.saturating_add(1);
+            continue;
+        };
+
+        if rss_url == feed_url && new_channel_id == channel_id {
+            unchanged = unchanged.saturating_add(1);
+            continue;
+        }
+
+        match db::update_channel_feed(&state.db_pool, &feed_url, &rss_url, new_channel_id).await {
+            Ok(()) => {
+                event!(
+                    Level::INFO,
+                    "Updated RSS feed URL of channel {name} from {feed_url} to {rss_url}"
+                );
+                updated = updated.saturating_add(1);
+            }
+            Err(e) => {
+                event!(Level::WARN, "{e:#}");
+                failed = failed.saturating_add(1);
+            }
+        }
+    }
+
+    (
+        axum::http::StatusCode::OK,
+        axum::Json(ChannelsRefreshFeedsResp {
+            status: format!(
+                "Refreshed RSS feed URLs of followed channels: {updated} updated, {unchanged} unchanged, {failed} failed"
             ),
+            updated,
+            unchanged,
+            failed,
+        }),
+    )
+}
+
+/// Handle a GET request listing the jobs waiting in the queue of this autotube
+/// instance, i.e., not yet taken off the queue by the worker, in the order they
+/// were enqueued. Jobs scheduled for later, such as retries or downloads of
+/// premieres, are listed last along with the time they are due at.
+pub(crate) async fn get_queue(
+    axum::extract::State(state): axum::extract::State<HTTPHandlerState>,
+) -> (axum::http::StatusCode, axum::Json<QueueResp>) {
+    match db::list_queue_entries(&state.read_pool, state.submit_job.instance()).await {
+        Ok(jobs) => (
+            axum::http::StatusCode::OK,
+            axum::Json(QueueResp {
+                status: format!("{} jobs waiting in queue", jobs.len()),
+                jobs,
+            }),
+        ),
+        Err(e) => {
+            event!(Level::WARN, "{e:#}");
             (
-                "http://www.youtube.com/watch?v=0123456789a",
-                "https://www.youtube.com/watch?v=0123456789a",
-            ),
+                axum::http::StatusCode::INTERNAL_SERVER_ERROR,
+                axum::Json(QueueResp {
+                    status: "Listing jobs waiting in queue failed".to_string(),
+                    jobs: Vec::new(),
+                }),
+            )
+        }
+    }
+}
+
+/// Handle a GET request listing the metadata records of videos published by
+/// channels followed in metadata-only mode, most recent first. If the optional
+/// `channel` query parameter contains a channel URL, only the records of that
+/// channel are listed.
+pub(crate) async fn get_channels_records(
+    axum::extract::State(state): axum::extract::State<HTTPHandlerState>,
+    axum::extract::Query(params): axum::extract::Query<ChannelRecordsReq>,
+) -> (axum::http::StatusCode, axum::Json<ChannelRecordsResp>) {
+    let records = match params.channel {
+        Some(channel) => {
+            let Some(channel_url) = canonical_youtube_channel_url(&channel) else {
+                return (
+                    axum::http::StatusCode::BAD_REQUEST,
+                    axum::Json(ChannelRecordsResp {
+                        status: "Unsupported or invalid channel URL".to_string(),
+                        records: Vec::new(),
+                    }),
+                );
+            };
+
+            sqlx::query_as::<_, ChannelRecord>(&format!(
+                "SELECT r.url, c.name AS channel, r.title, r.published, r.description, r.recorded_at
+                FROM {video_records} r
+                JOIN {channels} c ON r.feed_url = c.feed_url
+                WHERE c.name = $1
+                ORDER BY r.published DESC;",
+                video_records = db::table("video_records"),
+                channels = db::table("channels")
+            ))
+            .bind(channel_url)
+            .fetch_all(&state.read_pool)
+            .await
+        }
+        None => {
+            sqlx::query_as::<_, ChannelRecord>(&format!(
+            "SELECT r.url, c.name AS channel, r.title, r.published, r.description, r.recorded_at
+                FROM {video_records} r
+                JOIN {channels} c ON r.feed_url = c.feed_url
+                ORDER BY r.published DESC;",
+            video_records = db::table("video_records"),
+            channels = db::table("channels")
+        ))
+            .fetch_all(&state.read_pool)
+            .await
+        }
+    };
+
+    match records {
+        Ok(records) => (
+            axum::http::StatusCode::OK,
+            axum::Json(ChannelRecordsResp {
+                status: format!("Found {} video records", records.len()),
+                records,
+            }),
+        ),
+        Err(e) => {
+            event!(
+                Level::WARN,
+                "Retrieving video records from database failed: {e}"
+            );
             (
-                "https://www.youtube.com/watch?v=0123456789a",
-                "https://www.youtube.com/watch?v=0123456789a",
-            ),
+                axum::http::StatusCode::INTERNAL_SERVER_ERROR,
+                axum::Json(ChannelRecordsResp {
+                    status: "Retrieving video records from database failed".to_string(),
+                    records: Vec::new(),
+                }),
+            )
+        }
+    }
+}
+
+/// Handle a GET request for the raw RSS feed of the channel supplied in the
+/// `url` query parameter, exactly as autotube obtains it when checking the
+/// channel for new videos. The feed is passed through unparsed as XML, which
+/// helps figuring out why a check did or didn't find specific videos.
+pub(crate) async fn get_channels_feed(
+    axum::extract::State(state): axum::extract::State<HTTPHandlerState>,
+    axum::extract::Query(params): axum::extract::Query<ChannelFeedReq>,
+) -> axum::response::Response {
+    let (validated_url, channel_rss) = match validate_url(
+        &state.http_client,
+        state.page_attempts,
+        UrlKind::Channel,
+        &params.url,
+    )
+    .await
+    {
+        Ok(v) => (v.url, v.rss_url.unwrap_or_default()),
+        Err(e) => {
+            return (
+                axum::http::StatusCode::BAD_REQUEST,
+                axum::Json(ChannelFeedResp {
+                    status: e.to_string(),
+                }),
+            )
+                .into_response();
+        }
+    };
+    event!(
+        Level::DEBUG,
+        "Received valid channel URL to fetch RSS feed of: {validated_url}"
+    );
+
+    // The RSS feed is fetched via the same blocking request the worker uses, so
+    // move it off the async runtime.
+    match tokio::task::spawn_blocking(move || state.fetcher.fetch(&channel_rss)).await {
+        Ok(Ok(feed)) => (
+            axum::http::StatusCode::OK,
+            [(axum::http::header::CONTENT_TYPE, "application/xml")],
+            feed,
+        )
+            .into_response(),
+        Ok(Err(e)) => {
+            event!(
+                Level::WARN,
+                "Fetching RSS feed of channel {validated_url} failed: {e}"
+            );
             (
-                "https://www.youtube.com/watch?v=0123456789a&",
-                "https://www.youtube.com/watch?v=0123456789a",
-            ),
+                axum::http::StatusCode::BAD_GATEWAY,
+                axum::Json(ChannelFeedResp {
+                    status: "Fetching RSS feed of channel failed".to_string(),
+                }),
+            )
+                .into_response()
+        }
+        Err(e) => {
+            event!(
+                Level::WARN,
+                "Task fetching RSS feed of channel {validated_url} failed: {e}"
+            );
             (
-                "https://www.youtube.com/watch?v=0123456789a&other=ignored&more=alsoignored",
-                "https://www.youtube.com/watch?v=0123456789a",
-            ),
-        ];
+                axum::http::StatusCode::INTERNAL_SERVER_ERROR,
+                axum::Json(ChannelFeedResp {
+                    status: "Fetching RSS feed of channel failed".to_string(),
+                }),
+            )
+                .into_response()
+        }
+    }
+}
+
+/// Handle a POST request to retry the downloads of a channel's videos that failed
+/// for good and haven't been downloaded since, without checking the channel
+/// again. Each video is submitted as a new download job with the full number of
+/// attempts. Responds with the number of requeued downloads.
+pub(crate) async fn post_channels_retry_failed(
+    axum::extract::State(state): axum::extract::State<HTTPHandlerState>,
+    axum::Json(payload): axum::Json<ChannelRetryFailedReq>,
+) -> (axum::http::StatusCode, axum::Json<ChannelRetryFailedResp>) {
+    let (validated_url, channel_rss) = match validate_url(
+        &state.http_client,
+        state.page_attempts,
+        UrlKind::Channel,
+        &payload.url,
+    )
+    .await
+    {
+        Ok(v) => (v.url, v.rss_url.unwrap_or_default()),
+        Err(e) => {
+            return (
+                axum::http::StatusCode::BAD_REQUEST,
+                axum::Json(ChannelRetryFailedResp {
+                    status: e.to_string(),
+                    requeued: 0,
+                }),
+            );
+        }
+    };
+    let Some(channel_id) = channel_id_from_rss_url(&channel_rss) else {
+        return (
+            axum::http::StatusCode::BAD_REQUEST,
+            axum::Json(ChannelRetryFailedResp {
+                status: format!("Failed to determine channel ID of {validated_url}"),
+                requeued: 0,
+            }),
+        );
+    };
 
-        for (url, exp_ret) in &should_succeed {
-            assert!(
-                validate_youtube_url(YouTubeURL::Video, url)
-                    .await
-                    .is_ok_and(|(u, _)| u == *exp_ret)
+    let (settings, video_urls) =
+        match db::failed_channel_downloads(&state.read_pool, channel_id).await {
+            Ok(f) => f,
+            Err(e) => {
+                event!(Level::WARN, "{e:#}");
+                return (
+                    axum::http::StatusCode::INTERNAL_SERVER_ERROR,
+                    axum::Json(ChannelRetryFailedResp {
+                        status: e.to_string(),
+                        requeued: 0,
+                    }),
+                );
+            }
+        };
+
+    // Retried downloads carry the channel's current settings, if it is followed.
+    let options = DownloadOptions {
+        origin: DownloadOrigin::Channel,
+        cookies_file: settings.cookies_file,
+        min_height: settings.min_height.and_then(|h| u16::try_from(h).ok()),
+        channel_id: Some(channel_id.to_string()),
+        ..DownloadOptions::default()
+    };
+    let mut requeued: usize = 0;
+    for video_url in video_urls {
+        if (state
+            .submit_job
+            .send(Job::Download(JobDownloadVideo::with_options(
+                video_url,
+                options.clone(),
+            )))
+            .await)
+            .is_err()
+        {
+            event!(
+                Level::DEBUG,
+                "Retry of failed download could not be sent to queue for channel {validated_url}"
+            );
+            return (
+                axum::http::StatusCode::INTERNAL_SERVER_ERROR,
+                axum::Json(ChannelRetryFailedResp {
+                    status: "Failed to submit downloads to queue".to_string(),
+                    requeued,
+                }),
             );
         }
+        requeued = requeued.saturating_add(1);
+    }
+
+    event!(
+        Level::INFO,
+        "Requeued {requeued} failed downloads of channel {validated_url}"
+    );
+    (
+        axum::http::StatusCode::OK,
+        axum::Json(ChannelRetryFailedResp {
+            status: format!("Requeued {requeued} failed downloads of channel {validated_url}"),
+            requeued,
+        }),
+    )
+}
+
+/// Handle a POST request to download the back catalog of a channel, i.e., up to
+/// `max_videos` of its most recent videos (the configured maximum by default),
+/// skipping videos downloaded before. The channel doesn't need to be followed.
+/// Responds with the ID under which the backfill's progress is listed.
+pub(crate) async fn post_channels_backfill(
+    axum::extract::State(state): axum::extract::State<HTTPHandlerState>,
+    axum::Json(payload): axum::Json<ChannelBackfillReq>,
+) -> (axum::http::StatusCode, axum::Json<ChannelBackfillResp>) {
+    let max_videos = payload.max_videos.unwrap_or(state.backfill_max_videos);
+    if !(1..=state.backfill_max_videos).contains(&max_videos) {
+        return (
+            axum::http::StatusCode::BAD_REQUEST,
+            axum::Json(ChannelBackfillResp {
+                status: format!(
+                    "Field 'max_videos' needs to be between 1 and {}",
+                    state.backfill_max_videos
+                ),
+                id: None,
+            }),
+        );
+    }
+
+    let (validated_url, channel_rss) = match validate_url(
+        &state.http_client,
+        state.page_attempts,
+        UrlKind::Channel,
+        &payload.url,
+    )
+    .await
+    {
+        Ok(v) => (v.url, v.rss_url.unwrap_or_default()),
+        Err(e) => {
+            return (
+                axum::http::StatusCode::BAD_REQUEST,
+                axum::Json(ChannelBackfillResp {
+                    status: e.to_string(),
+                    id: None,
+                }),
+            );
+        }
+    };
+
+    // Only one backfill of a channel runs at a time per autotube instance.
+    let id = format!("{:016x}", rand::random::<u64>());
+    match db::insert_backfill(
+        &state.db_pool,
+        &id,
+        state.submit_job.instance(),
+        &channel_rss,
+        max_videos,
+    )
+    .await
+    {
+        Ok(true) => {}
+        Ok(false) => {
+            return (
+                axum::http::StatusCode::CONFLICT,
+                axum::Json(ChannelBackfillResp {
+                    status: format!("Channel {validated_url} is already being backfilled"),
+                    id: None,
+                }),
+            );
+        }
+        Err(e) => {
+            event!(Level::WARN, "{e:#}");
+            return (
+                axum::http::StatusCode::INTERNAL_SERVER_ERROR,
+                axum::Json(ChannelBackfillResp {
+                    status: e.to_string(),
+                    id: None,
+                }),
+            );
+        }
+    }
+
+    if (state
+        .submit_job
+        .send(Job::Backfill(JobBackfillChannel::new(
+            id.clone(),
+            channel_rss,
+            max_videos,
+        )))
+        .await)
+        .is_err()
+    {
+        event!(
+            Level::DEBUG,
+            "Backfill of channel could not be sent to queue: {validated_url}"
+        );
+        if let Err(e) = db::finish_backfill(&state.db_pool, &id, "failed").await {
+            event!(Level::WARN, "{e:#}");
+        }
+        return (
+            axum::http::StatusCode::INTERNAL_SERVER_ERROR,
+            axum::Json(ChannelBackfillResp {
+                status: "Backfill of channel could not be sent to queue".to_string(),
+                id: None,
+            }),
+        );
+    }
+
+    (
+        axum::http::StatusCode::CREATED,
+        axum::Json(ChannelBackfillResp {
+            status: format!(
+                "Started backfilling up to {max_videos} videos of channel {validated_url}"
+            ),
+            id: Some(id),
+        }),
+    )
+}
+
+/// Handle a GET request listing all backfills of channels along with their
+/// progress, most recently started first.
+pub(crate) async fn get_channels_backfill(
+    axum::extract::State(state): axum::extract::State<HTTPHandlerState>,
+) -> (axum::http::StatusCode, axum::Json<ChannelsBackfillsResp>) {
+    match db::list_backfills(&state.read_pool).await {
+        Ok(backfills) => (
+            axum::http::StatusCode::OK,
+            axum::Json(ChannelsBackfillsResp {
+                status: format!("{} backfills", backfills.len()),
+                backfills,
+            }),
+        ),
+        Err(e) => {
+            event!(Level::WARN, "{e:#}");
+            (
+                axum::http::StatusCode::INTERNAL_SERVER_ERROR,
+                axum::Json(ChannelsBackfillsResp {
+                    status: "Listing backfills failed".to_string(),
+                    backfills: Vec::new(),
+                }),
+            )
+        }
     }
 }
@@ -1,21 +1,83 @@
-use crate::handlers::{HTTPHandlerState, post_channels_follow, post_downloads_ondemand};
-use crate::jobs::Job;
+use crate::handlers::{
+    HTTPHandlerState, get_channels, get_channels_backfill, get_channels_feed, get_channels_records,
+    get_downloads_active, get_downloads_status, get_healthz, get_healthz_details, get_queue,
+    get_stats_failures, patch_channels_name, post_channels_backfill, post_channels_follow,
+    post_channels_follow_bulk, post_channels_refresh_feeds, post_channels_retry_failed,
+    post_channels_unfollow, post_downloads_ondemand, post_validate,
+};
+use crate::jobs::{FormatConstraints, JobQueue};
+use crate::rss::{FEED_MAX_VIDEOS, FeedFetcher};
 use crate::trigger::TriggerState;
-use crate::worker::WorkerState;
-use clap::Parser;
+use crate::worker::{
+    ActiveDownloads, CollisionStrategy, DependencyPolicy, FollowOrder, Heartbeat, LastCheckedInit,
+    PostprocessingPolicy, WorkerConfig, WorkerState, missing_dependencies,
+};
+use clap::{CommandFactory, Parser};
+use tower_http::compression::Predicate;
+use tower_http::compression::predicate::{NotForContentType, SizeAbove};
 use tracing::{Level, event};
 use tracing_subscriber::prelude::*;
 
+mod cli;
 mod db;
 mod handlers;
 mod jobs;
+mod platforms;
 mod rss;
 mod trigger;
 mod worker;
 
+// Responses with bodies smaller than this number of bytes are sent uncompressed,
+// as compressing them would save next to nothing.
+const COMPRESSION_MIN_BYTES: u16 = 1024;
+
+// User agent of a common desktop browser, which autotube identifies itself with
+// towards YouTube unless configured otherwise.
+const DEFAULT_USER_AGENT: &str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/131.0.0.0 Safari/537.36";
+
 #[derive(Debug, Parser)]
-#[command(about, author, version, next_line_help = true)]
-struct Args {
+#[command(about, author, version, propagate_version = true)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    /// Only log warnings and errors. Ignored if `RUST_LOG` is set.
+    quiet: bool,
+
+    #[arg(short, long, global = true, action = clap::ArgAction::Count)]
+    /// Log debug messages as well, or also trace messages if given twice
+    /// ('-vv'). Ignored if `RUST_LOG` is set.
+    verbose: u8,
+}
+
+#[derive(Debug, clap::Subcommand)]
+enum Command {
+    #[command(next_line_help = true)]
+    /// Run the HTTP server and the background worker executing submitted jobs
+    /// and checking followed channels. This is the default if no subcommand is
+    /// given.
+    Serve(ServeArgs),
+
+    #[command(next_line_help = true)]
+    /// Start following a channel and carry out its initial downloads.
+    Follow(cli::FollowArgs),
+
+    #[command(next_line_help = true)]
+    /// Check followed channels for new videos and download them.
+    Check(cli::CheckArgs),
+
+    #[command(next_line_help = true)]
+    /// List the followed channels.
+    List(cli::ListArgs),
+
+    #[command(next_line_help = true)]
+    /// Download a single video.
+    Download(cli::DownloadArgs),
+}
+
+#[derive(Debug, clap::Args)]
+struct ServeArgs {
     #[arg(long, env, default_value = "127.0.0.1")]
     /// The IP address the HTTP listener will bind to.
     listen_ip: String,
@@ -29,6 +91,165 @@ struct Args {
     /// order for a request to be deemed authorized.
     bearer_token: String,
 
+    #[arg(long, env, value_delimiter = ',')]
+    /// Origin (scheme, host, and port) of a browser-based frontend that is
+    /// permitted to make cross-origin requests to autotube. Can be supplied
+    /// multiple times or as a comma-separated list. Cross-origin requests are
+    /// rejected by browsers if no origin is configured.
+    cors_origin: Vec<String>,
+
+    #[arg(long, env, default_value = "0.5", value_parser = trigger::parse_check_spread)]
+    /// Fraction of each check interval (greater than 0.0, at most 1.0) across
+    /// which the checks of all channels followed with that frequency are spread.
+    /// Set to 1.0 to spread them across the full interval.
+    check_spread: f64,
+
+    #[arg(long, env, default_value = "0.5", value_parser = trigger::parse_check_jitter)]
+    /// Proportion (0.0 to 1.0) of the average time between two channel checks by
+    /// which each check is randomly shifted in either direction, making the
+    /// pattern of RSS feed requests less regular.
+    check_jitter: f64,
+
+    #[arg(long, env, value_delimiter = ',', value_parser = trigger::parse_frequency_check_spread)]
+    /// Check spread for the channels of one frequency only, overriding
+    /// '--check-spread', e.g., 'often=0.2'. Can be supplied multiple times or as
+    /// a comma-separated list.
+    check_spread_for: Vec<(trigger::Frequencies, f64)>,
+
+    #[arg(long, env, value_delimiter = ',', value_parser = trigger::parse_frequency_check_jitter)]
+    /// Check jitter for the channels of one frequency only, overriding
+    /// '--check-jitter', e.g., 'rarely=1.0'. Can be supplied multiple times or as
+    /// a comma-separated list.
+    check_jitter_for: Vec<(trigger::Frequencies, f64)>,
+
+    #[arg(long, env)]
+    /// Check all followed channels right after starting up, catching up on videos
+    /// published while autotube wasn't running, instead of waiting for the first
+    /// scheduled checks.
+    check_on_startup: bool,
+
+    #[arg(long, env, default_value = "60")]
+    /// Maximum number of seconds by which the first scheduled checks of each check
+    /// frequency are randomly delayed after starting up, so that they don't all
+    /// start at once. Set to 0 to start them right away.
+    startup_jitter_secs: u32,
+
+    #[arg(long, env)]
+    /// Delay scheduled channel checks for as long as more than this many
+    /// downloads are in progress, so that the running downloads get the
+    /// bandwidth. Checks aren't throttled if not set.
+    throttle_checks_above: Option<u16>,
+
+    #[arg(long, env, default_value = "600", value_parser = clap::value_parser!(u32).range(1..))]
+    /// Number of seconds after which the worker is considered stalled if jobs are
+    /// waiting in the queue but none was taken off it. Reported by '/healthz'.
+    stall_secs: u32,
+
+    #[arg(long, env, default_value = "30", value_parser = clap::value_parser!(u32).range(1..))]
+    /// Number of seconds autotube waits for running jobs and background tasks to
+    /// finish after being told to shut down. Once exceeded, autotube logs what is
+    /// still running and exits right away.
+    shutdown_timeout_secs: u32,
+
+    #[command(flatten)]
+    following: FollowingArgs,
+
+    #[command(flatten)]
+    db: DbArgs,
+
+    #[command(flatten)]
+    worker: WorkerArgs,
+}
+
+// Configuration of the database, shared by all subcommands. Field names double
+// as names of command line options, hence the common prefix.
+#[allow(clippy::struct_field_names)]
+#[derive(Debug, clap::Args)]
+struct DbArgs {
+    #[arg(long, env, default_value = "sqlite:autotube.db?mode=rwc")]
+    /// URL of the database autotube keeps its state in. The URL scheme selects
+    /// the backend: 'sqlite:' for a local `SQLite` database file, 'postgres:' or
+    /// 'postgresql:' for a (potentially shared) `PostgreSQL` database.
+    db_url: String,
+
+    #[arg(long, env, default_value = "10", value_parser = clap::value_parser!(u32).range(1..))]
+    /// Maximum number of connections to the database kept open at once. Jobs
+    /// and HTTP requests needing a connection while all are in use wait for one
+    /// to become available.
+    db_max_connections: u32,
+
+    #[arg(long, env)]
+    /// URL of the database to serve read-only HTTP requests (e.g., listing
+    /// channels or downloads) from, such as a read replica of the database at
+    /// '--db-url'. Connections to it are kept in a pool of their own.
+    db_read_url: Option<String>,
+
+    #[arg(long, env, value_parser = clap::value_parser!(u32).range(1..))]
+    /// Maximum number of connections in a separate pool serving read-only HTTP
+    /// requests, so that they don't contend with jobs for connections. Defaults to
+    /// '--db-max-connections' if '--db-read-url' is set. Without either option,
+    /// read-only HTTP requests share the pool of all other database accesses.
+    db_read_max_connections: Option<u32>,
+
+    #[arg(long, env, default_value = "", value_parser = db::parse_table_prefix)]
+    /// Prefix prepended to the names of all tables autotube keeps its state in,
+    /// e.g., 'autotube_' to keep them apart from tables of other applications
+    /// sharing the database. May only contain ASCII letters, digits, and
+    /// underscores. Tables aren't prefixed by default.
+    db_table_prefix: String,
+}
+
+impl DbArgs {
+    // Apply the configured table prefix, then connect to the database at
+    // '--db-url' and create autotube's tables in it if they don't exist yet.
+    async fn init_db(&self) -> anyhow::Result<sqlx::AnyPool> {
+        db::set_table_prefix(&self.db_table_prefix)?;
+        db::init_db(&self.db_url, self.db_max_connections).await
+    }
+
+    // Return the pool of connections serving read-only HTTP requests as configured,
+    // which is the supplied pool of all other database accesses by default.
+    async fn read_pool(&self, db_pool: &sqlx::AnyPool) -> anyhow::Result<sqlx::AnyPool> {
+        if self.db_read_url.is_none() && self.db_read_max_connections.is_none() {
+            return Ok(db_pool.clone());
+        }
+
+        db::connect_db(
+            self.db_read_url.as_deref().unwrap_or(&self.db_url),
+            self.db_read_max_connections
+                .unwrap_or(self.db_max_connections),
+        )
+        .await
+    }
+}
+
+// Configuration of how channels are followed, shared by all subcommands that
+// follow channels.
+#[derive(Debug, clap::Args)]
+struct FollowingArgs {
+    #[arg(long, env, default_value = "0")]
+    /// Number of most recent videos to download when starting to follow a channel,
+    /// used if a follow request doesn't specify `download_as_of` itself. Values
+    /// above 15 require '--playlist-backfill', as the RSS feed lists no more.
+    default_download_as_of: u8,
+
+    #[arg(long, env)]
+    /// File system path to the directory in which the avatar and banner images of
+    /// channels are archived when following them. Images aren't archived if unset.
+    channel_images_dir: Option<String>,
+
+    #[arg(long, env)]
+    /// File system path to the directory holding the cookies files that may be
+    /// associated with individual channels when following them. Follow requests
+    /// naming a cookies file are rejected if unset.
+    cookies_dir: Option<String>,
+}
+
+// Configuration of how URLs are validated and jobs are executed, shared by all
+// subcommands that run jobs.
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Debug, clap::Args)]
+struct WorkerArgs {
     #[arg(long, env)]
     /// File system path to the location of the video directory in which videos
     /// will be placed after they have been downloaded successfully.
@@ -38,51 +259,837 @@ struct Args {
     /// File system path underneath which autotube will create temporary
     /// directories for individual video download attempts.
     tmp_dir: String,
+
+    #[arg(long, env, value_parser = jobs::parse_audio_lang)]
+    /// Language code (e.g., 'en', 'de', 'pt-BR') of the audio track to prefer
+    /// for videos offering multiple audio tracks. Videos without an audio track
+    /// in this language are downloaded with their default audio track.
+    audio_lang: Option<String>,
+
+    #[arg(long, env, value_parser = jobs::parse_audio_format)]
+    /// Audio format (e.g., 'mp3', 'opus') into which 'yt-dlp' extracts the audio
+    /// of downloaded videos, keeping only the audio file. 'best' keeps the best
+    /// audio stream without converting it. Videos are kept as they are if unset.
+    extract_audio: Option<String>,
+
+    #[arg(long, env, value_parser = jobs::parse_audio_quality, requires = "extract_audio")]
+    /// Quality of extracted audio, either a VBR quality from 0 (best) to 10
+    /// (worst) or a bitrate such as '128K'. Defaults to 'yt-dlp''s default.
+    audio_quality: Option<String>,
+
+    #[arg(long, env, default_value = "0")]
+    /// Number of additional days on which autotube tries to download a video
+    /// again after all attempts of a day have failed, e.g., because the video is
+    /// temporarily unavailable. Each day starts with a fresh set of attempts.
+    retry_days: u8,
+
+    #[arg(long, env, value_parser = jobs::parse_cookies_file)]
+    /// File system path to a Netscape-formatted cookies file that 'yt-dlp' uses
+    /// to authenticate video downloads. Cookies files associated with individual
+    /// channels take precedence for downloads of their videos.
+    cookies_file: Option<String>,
+
+    #[arg(long, env, value_parser = jobs::parse_netrc_file, conflicts_with = "cookies_file")]
+    /// File system path to a netrc file holding the credentials that 'yt-dlp'
+    /// uses to authenticate video downloads, as an alternative to a cookies file.
+    /// Cookies files associated with individual channels take precedence for
+    /// downloads of their videos.
+    netrc_location: Option<String>,
+
+    #[arg(long, env)]
+    /// When following a channel with more initial downloads requested than its
+    /// RSS feed lists (at most 15 videos), enumerate the channel's most recent
+    /// videos via 'yt-dlp' on the channel's uploads playlist instead.
+    playlist_backfill: bool,
+
+    #[arg(long, env, default_value = DEFAULT_USER_AGENT)]
+    /// User agent autotube identifies itself with when fetching channel webpages
+    /// and RSS feeds as well as when 'yt-dlp' downloads videos.
+    user_agent: String,
+
+    #[arg(long, env, value_parser = jobs::parse_proxy)]
+    /// URL of a proxy (scheme 'http', 'https', 'socks5', or 'socks5h') through which
+    /// 'yt-dlp' downloads videos and lists channel playlists. By default, 'yt-dlp'
+    /// connects directly.
+    download_proxy: Option<String>,
+
+    #[arg(long, env, value_parser = jobs::parse_proxy)]
+    /// URL of a proxy (scheme 'http', 'https', 'socks5', or 'socks5h') through which
+    /// autotube fetches RSS feeds and channel webpages, e.g., when validating URLs.
+    /// By default, autotube connects directly.
+    rss_proxy: Option<String>,
+
+    #[arg(long, env, value_parser = jobs::parse_http_header)]
+    /// Additional HTTP request header of the form 'Key: Value' that autotube sends
+    /// when fetching RSS feeds and channel webpages, e.g., to authenticate with a
+    /// proxy or caching layer. Can be supplied multiple times. Not passed on to
+    /// 'yt-dlp'.
+    http_header: Vec<(reqwest::header::HeaderName, reqwest::header::HeaderValue)>,
+
+    #[arg(long, env, default_value = "3", value_parser = clap::value_parser!(u8).range(1..))]
+    /// Number of attempts at fetching a channel's RSS feed before giving up on
+    /// the current check of the channel. Retries wait for an exponentially
+    /// growing backoff, starting at 2 seconds.
+    feed_attempts: u8,
+
+    #[arg(long, env, default_value = "4", value_parser = clap::value_parser!(u16).range(1..))]
+    /// Maximum number of RSS feeds fetched concurrently from the same host. As
+    /// all followed channels currently share host 'www.youtube.com', this effectively
+    /// limits the number of concurrent RSS feed requests overall.
+    feed_max_per_host: u16,
+
+    #[arg(long, env)]
+    /// Compute the SHA-256 checksum of each downloaded video and store it with
+    /// the record of the download. Hashing large videos costs CPU time.
+    compute_checksums: bool,
+
+    #[arg(long, env)]
+    /// Restrict the names of downloaded videos to ASCII letters, digits, '-',
+    /// '_', and '.', replacing all other characters by '_'. Also passed on to
+    /// 'yt-dlp'. Useful for storage backends with restrictive file systems.
+    restrict_filenames: bool,
+
+    #[arg(long, env, default_value = "255", value_parser = clap::value_parser!(u16).range(32..))]
+    /// Maximum length in bytes of the names of downloaded videos. Longer names
+    /// are truncated, preserving their file extension.
+    max_filename_bytes: u16,
+
+    #[arg(long, env, value_parser = jobs::parse_file_mode)]
+    /// Unix permission mode in octal notation (e.g., '0644') that downloaded
+    /// videos are given in the video directory, e.g., so that a media server
+    /// running as another user can read them. By default, videos keep the mode
+    /// they were created with, which depends on the umask.
+    video_file_mode: Option<u32>,
+
+    #[arg(long, env, value_parser = jobs::parse_merge_output_format)]
+    /// Container format (e.g., 'mkv', 'mp4') into which 'yt-dlp' merges separately
+    /// downloaded video and audio streams. Also determines the file extension of
+    /// such downloaded videos. By default, 'yt-dlp' picks a suitable container.
+    merge_output_format: Option<String>,
+
+    #[arg(long = "extractor-args", env = "EXTRACTOR_ARGS", value_name = "EXTRACTOR_ARGS", value_parser = jobs::parse_extractor_args)]
+    /// Extractor arguments passed verbatim to 'yt-dlp' when downloading videos,
+    /// e.g., `youtube:player_client=android` to work around throttling. Can be
+    /// supplied multiple times, but only once via the environment variable.
+    extractor_arguments: Vec<String>,
+
+    #[arg(long = "parse-metadata", env = "PARSE_METADATA", value_name = "PARSE_METADATA", value_parser = jobs::parse_metadata_mapping)]
+    /// Metadata mapping of the form '[WHEN:]FROM:TO' passed verbatim to 'yt-dlp'
+    /// as '--parse-metadata' when downloading videos, e.g., '%(uploader)s:%(artist)s'
+    /// to tag videos with their channel's name as artist. Can be supplied multiple
+    /// times, but only once via the environment variable.
+    parse_metadata: Vec<String>,
+
+    #[arg(long = "replace-in-metadata", env = "REPLACE_IN_METADATA", value_name = "REPLACE_IN_METADATA", value_parser = jobs::parse_metadata_replacement)]
+    /// Metadata replacement of the form 'FIELDS REGEX REPLACE' passed to 'yt-dlp'
+    /// as '--replace-in-metadata' when downloading videos, applied after all
+    /// metadata mappings. Can be supplied multiple times, but only once via the
+    /// environment variable.
+    replace_in_metadata: Vec<String>,
+
+    #[arg(long, env, value_parser = jobs::parse_format_sort)]
+    /// Order (e.g., 'res:1080,codec:av01') in which 'yt-dlp' ranks the available
+    /// formats of a video to pick the one to download, passed on as '-S'. By
+    /// default, 'yt-dlp' uses its built-in order.
+    format_sort: Option<String>,
+
+    #[arg(long, env, value_delimiter = ',', value_parser = jobs::parse_video_codec)]
+    /// Video codecs ('h264', 'h265', 'vp9', 'av1') that downloaded videos may be
+    /// encoded with. Can be supplied multiple times or as a comma-separated list.
+    /// Videos not offered in any accepted format are skipped. Accepts any codec if
+    /// unset.
+    accepted_video_codecs: Vec<String>,
+
+    #[arg(long, env, value_delimiter = ',', value_parser = jobs::parse_audio_codec)]
+    /// Audio codecs ('aac', 'opus', 'vorbis', 'mp3') that the audio of downloaded
+    /// videos may be encoded with. Can be supplied multiple times or as a
+    /// comma-separated list. Videos not offered in any accepted format are
+    /// skipped. Accepts any codec if unset.
+    accepted_audio_codecs: Vec<String>,
+
+    #[arg(long, env, value_parser = jobs::parse_container)]
+    /// Container ('mp4', 'webm') that the streams of downloaded videos need to come
+    /// in. Videos not offered in the accepted container are skipped. Accepts any
+    /// container if unset.
+    accepted_container: Option<String>,
+
+    #[arg(long, env, default_value = "{published}_{downloaded}", value_parser = jobs::parse_filename_template)]
+    /// Template of the names of videos downloaded on demand, which may contain the
+    /// placeholders '{published}' and '{downloaded}' (timestamps of publication and
+    /// download), '{title}', and '{id}'. A name requested with the download takes
+    /// precedence.
+    ondemand_filename_template: String,
+
+    #[arg(long, env, default_value = "{published}_{downloaded}", value_parser = jobs::parse_filename_template)]
+    /// Template of the names of videos downloaded from followed channels, with the
+    /// same placeholders as '--ondemand-filename-template'.
+    channel_filename_template: String,
+
+    #[arg(long, env, default_value = "1", value_parser = clap::value_parser!(u8).range(1..=16))]
+    /// Number of fragments of fragmented formats that 'yt-dlp' downloads at once,
+    /// which speeds up downloads of large videos on fast connections.
+    concurrent_fragments: u8,
+
+    #[arg(long, env, value_parser = clap::value_parser!(u16).range(1..))]
+    /// Maximum number of new videos a single check of a channel downloads. If a
+    /// check finds more, the most recent ones are downloaded and the others are
+    /// skipped. Downloads all new videos if unset.
+    max_per_check: Option<u16>,
+
+    #[arg(long, env, value_parser = clap::value_parser!(u32).range(1..))]
+    /// Maximum number of downloads started per day, counting each attempt,
+    /// e.g., to stay below rate limits during large backfills. Further downloads
+    /// are deferred to the next day. Not limited if unset.
+    max_downloads_per_day: Option<u32>,
+
+    #[arg(long, env, default_value = "+00:00", value_parser = jobs::parse_utc_offset)]
+    /// Offset from UTC (e.g., '+02:00') of the time zone in whose midnight the
+    /// count of downloads per day starts over.
+    download_day_utc_offset: chrono::FixedOffset,
+
+    #[arg(long, env)]
+    /// File system path to a manifest file to which a line of the form
+    /// '<TIMESTAMP>\t<URL>\t<PATH>' is appended for each completed download,
+    /// e.g., for external indexers to pick up new videos. Created if missing.
+    manifest_file: Option<String>,
+
+    #[arg(long, env, value_enum, default_value_t = FollowOrder::Newest)]
+    /// Order in which the initial downloads of a newly followed channel are
+    /// queued.
+    follow_order: FollowOrder,
+
+    #[arg(long, env, value_enum, default_value_t = FollowOrder::Newest)]
+    /// Order in which the downloads of the new videos a check of a channel finds
+    /// are queued.
+    check_order: FollowOrder,
+
+    #[arg(long, env, value_enum, default_value_t = LastCheckedInit::Now)]
+    /// Point in time a newly followed channel is considered last checked at, i.e.,
+    /// as of which its first check looks for new videos.
+    last_checked_init: LastCheckedInit,
+
+    #[arg(long, env, value_enum, default_value_t = CollisionStrategy::RenameSuffix)]
+    /// How to handle a downloaded video whose intended file name is already taken
+    /// in the video directory.
+    on_collision: CollisionStrategy,
+
+    #[arg(long, env, value_enum, default_value_t = PostprocessingPolicy::Keep)]
+    /// How to handle a downloaded video one of whose post-processing steps (e.g.,
+    /// embedding the thumbnail) failed.
+    on_postprocessing_error: PostprocessingPolicy,
+
+    #[arg(long, env)]
+    /// Retry downloads of videos that aren't available in this country once with
+    /// 'yt-dlp' faking the origin of the request. Without this, such a retry is
+    /// only made if downloads go through a proxy.
+    geo_bypass: bool,
+
+    #[arg(long, env, default_value = "10", value_parser = clap::value_parser!(u16).range(1..))]
+    /// Number of downloads a backfill of a channel's back catalog queues at once.
+    /// The next batch is only queued once all downloads of the previous one ended.
+    backfill_batch_size: u16,
+
+    #[arg(long, env, default_value = "1000", value_parser = clap::value_parser!(u32).range(1..))]
+    /// Maximum number of videos a single backfill of a channel's back catalog may
+    /// download, counting from the most recent one. Requests may ask for fewer.
+    backfill_max_videos: u32,
+
+    #[arg(long, env, value_enum, default_value_t = DependencyPolicy::Pause)]
+    /// What to do if 'yt-dlp' or 'ffmpeg' vanish while autotube is running, which
+    /// is checked every minute.
+    on_missing_dependency: DependencyPolicy,
+
+    #[arg(long, env, default_value = "3", value_parser = clap::value_parser!(u8).range(1..=5))]
+    /// Number of attempts at fetching a channel's webpage when validating its URL,
+    /// with a doubling backoff starting at 500 milliseconds in between. Capped at
+    /// 5 to keep the latency of follow requests bounded.
+    page_attempts: u8,
+}
+
+// Construct the CORS layer permitting browsers to make requests to autotube
+// from the supplied origins, including preflight requests. Returns `None` if no
+// origins were supplied, in which case browsers enforce their same-origin
+// policy as usual.
+fn build_cors_layer(origins: &[String]) -> anyhow::Result<Option<tower_http::cors::CorsLayer>> {
+    if origins.is_empty() {
+        return Ok(None);
+    }
+
+    let mut allowed_origins = Vec::with_capacity(origins.len());
+    for origin in origins {
+        let Ok(o) = axum::http::HeaderValue::from_str(origin.trim()) else {
+            return Err(anyhow::anyhow!("Invalid CORS origin '{origin}'"));
+        };
+        allowed_origins.push(o);
+    }
+
+    Ok(Some(
+        tower_http::cors::CorsLayer::new()
+            .allow_origin(allowed_origins)
+            .allow_methods([
+                axum::http::Method::GET,
+                axum::http::Method::POST,
+                axum::http::Method::PATCH,
+            ])
+            .allow_headers([
+                axum::http::header::AUTHORIZATION,
+                axum::http::header::CONTENT_TYPE,
+            ]),
+    ))
+}
+
+// Compare two byte strings in time depending only on their lengths, not on their
+// contents, so that the time taken to reject a bearer token doesn't reveal how
+// much of it was guessed correctly.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter()
+        .zip(b)
+        .fold(0_u8, |diff, (x, y)| std::hint::black_box(diff | (x ^ y)))
+        == 0
+}
+
+// Determine whether the supplied request headers carry the configured bearer
+// token in their 'Authorization' header.
+fn bearer_token_matches(headers: &axum::http::HeaderMap, bearer_token: &str) -> bool {
+    headers
+        .get(axum::http::header::AUTHORIZATION)
+        .and_then(|v| v.as_bytes().strip_prefix(b"Bearer "))
+        .is_some_and(|token| constant_time_eq(token, bearer_token.as_bytes()))
+}
+
+#[derive(Clone)]
+// Rejects requests that don't carry the configured bearer token with status
+// '401 Unauthorized'.
+struct BearerAuth(String);
+
+impl<B> tower_http::validate_request::ValidateRequest<B> for BearerAuth {
+    type ResponseBody = axum::body::Body;
+
+    fn validate(
+        &mut self,
+        req: &mut axum::http::Request<B>,
+    ) -> Result<(), axum::http::Response<Self::ResponseBody>> {
+        if bearer_token_matches(req.headers(), &self.0) {
+            Ok(())
+        } else {
+            Err(axum::response::IntoResponse::into_response(
+                axum::http::StatusCode::UNAUTHORIZED,
+            ))
+        }
+    }
+}
+
+// Build the HTTP router handling incoming client requests on all endpoints
+// autotube services. Note that we assume to be running behind a security
+// perimeter (e.g., WireGuard), so that the bearer token merely serves as an
+// additional line of defense.
+fn build_router(
+    handler_state: HTTPHandlerState,
+    bearer_token: &str,
+    cors_layer: Option<tower_http::cors::CorsLayer>,
+) -> axum::Router {
+    // `ValidateRequestHeaderLayer::bearer` is deprecated upstream for being "too
+    // basic to be useful in real applications". A single static bearer token is
+    // all the extra protection we want here, though, so silence the warning.
+    #[allow(deprecated)]
+    let router = axum::Router::new()
+        .without_v07_checks()
+        .route(
+            "/downloads/ondemand",
+            axum::routing::post(post_downloads_ondemand),
+        )
+        .route(
+            "/downloads/status",
+            axum::routing::get(get_downloads_status),
+        )
+        .route(
+            "/downloads/active",
+            axum::routing::get(get_downloads_active),
+        )
+        .route("/channels", axum::routing::get(get_channels))
+        .route(
+            "/channels/follow",
+            axum::routing::post(post_channels_follow),
+        )
+        .route(
+            "/channels/follow/bulk",
+            axum::routing::post(post_channels_follow_bulk),
+        )
+        .route(
+            "/channels/unfollow",
+            axum::routing::post(post_channels_unfollow),
+        )
+        .route("/channels/name", axum::routing::patch(patch_channels_name))
+        .route(
+            "/channels/refresh-feeds",
+            axum::routing::post(post_channels_refresh_feeds),
+        )
+        .route(
+            "/channels/records",
+            axum::routing::get(get_channels_records),
+        )
+        .route("/channels/feed", axum::routing::get(get_channels_feed))
+        .route(
+            "/channels/retry-failed",
+            axum::routing::post(post_channels_retry_failed),
+        )
+        .route(
+            "/channels/backfill",
+            axum::routing::get(get_channels_backfill).post(post_channels_backfill),
+        )
+        .route("/validate", axum::routing::post(post_validate))
+        .route("/queue", axum::routing::get(get_queue))
+        .route("/stats/failures", axum::routing::get(get_stats_failures))
+        .route("/healthz/details", axum::routing::get(get_healthz_details))
+        .layer(
+            tower_http::compression::CompressionLayer::new().compress_when(
+                SizeAbove::new(COMPRESSION_MIN_BYTES)
+                    .and(NotForContentType::GRPC)
+                    .and(NotForContentType::IMAGES)
+                    .and(NotForContentType::SSE),
+            ),
+        )
+        .layer(
+            tower_http::validate_request::ValidateRequestHeaderLayer::custom(BearerAuth(
+                bearer_token.to_string(),
+            )),
+        )
+        // Liveness probes by monitoring systems don't need to authenticate.
+        .route("/healthz", axum::routing::get(get_healthz))
+        .with_state(handler_state);
+
+    // If configured, wrap all routes in the CORS layer. It needs to be the outermost
+    // layer, so that preflight requests (which never carry the bearer token) are
+    // answered before reaching the authorization check.
+    match cors_layer {
+        Some(cors) => router.layer(cors),
+        None => router,
+    }
+}
+
+// Everything a shutdown waits for, so that a shutdown exceeding its deadline can
+// report what kept it from completing.
+struct ShutdownDeadline {
+    timeout_secs: u32,
+    tasks: Vec<(&'static str, tokio::task::AbortHandle)>,
+    active_downloads: ActiveDownloads,
+}
+
+impl ShutdownDeadline {
+    // Start the countdown to the deadline on a thread of its own, as the async
+    // runtime itself might be unable to shut down, e.g., while waiting for blocking
+    // downloads. Once the deadline passes, log the background tasks and downloads
+    // still running and exit the process.
+    fn arm(self) {
+        std::thread::spawn(move || {
+            std::thread::sleep(std::time::Duration::from_secs(u64::from(self.timeout_secs)));
+
+            let tasks = self
+                .tasks
+                .iter()
+                .filter(|(_, handle)| !handle.is_finished())
+                .map(|(name, _)| *name)
+                .collect::<Vec<&str>>();
+            let downloads = self
+                .active_downloads
+                .lock()
+                .map(|active| {
+                    active
+                        .values()
+                        .map(|d| d.url.clone())
+                        .collect::<Vec<String>>()
+                })
+                .unwrap_or_default();
+            event!(
+                Level::ERROR,
+                "Shutdown didn't complete within {} seconds, exiting anyway (tasks still running: {}; downloads still running: {})",
+                self.timeout_secs,
+                if tasks.is_empty() {
+                    "none".to_string()
+                } else {
+                    tasks.join(", ")
+                },
+                if downloads.is_empty() {
+                    "none".to_string()
+                } else {
+                    downloads.join(", ")
+                },
+            );
+            std::process::exit(1);
+        });
+    }
 }
 
 // Wait to observe the ctrl+c signal and cause everything to shut down properly
 // by dropping the sender half of a broadcast channel (all receivers will close
-// upon this event).
-async fn shutdown_upon_signal(send_shutdown: tokio::sync::broadcast::Sender<()>) {
+// upon this event). From then on, the shutdown has to complete before the
+// supplied deadline.
+async fn shutdown_upon_signal(
+    send_shutdown: tokio::sync::broadcast::Sender<()>,
+    deadline: ShutdownDeadline,
+) {
     let _ = tokio::signal::ctrl_c().await;
     event!(Level::INFO, "Received signal to shut down gracefully");
+    deadline.arm();
     drop(send_shutdown);
 }
 
+// Number of seconds to wait for a connection to a configured proxy when checking
+// whether it is reachable at startup.
+const PROXY_PROBE_SECS: u64 = 3;
+
+#[derive(Debug)]
+// A problem with the configuration found at startup. Fatal problems keep
+// autotube from starting, others are merely reported.
+struct ConfigProblem {
+    fatal: bool,
+    message: String,
+}
+
+// Check whether the supplied path names an existing directory autotube may write
+// to, describing the problem otherwise.
+fn check_dir(option: &str, dir: &str) -> Option<String> {
+    match std::fs::metadata(dir) {
+        Ok(m) if !m.is_dir() => Some(format!("'{option}' {dir} is not a directory")),
+        Ok(m) if m.permissions().readonly() => Some(format!("'{option}' {dir} is not writable")),
+        Ok(_) => None,
+        Err(e) => Some(format!("'{option}' {dir} is not accessible: {e}")),
+    }
+}
+
+// Check whether a TCP connection to the host of the supplied proxy URL can be
+// established, describing the problem otherwise.
+fn check_proxy(option: &str, proxy: &str) -> Option<String> {
+    let Ok(url) = reqwest::Url::parse(proxy) else {
+        return Some(format!("'{option}' {proxy} is not a valid URL"));
+    };
+    let port = url.port_or_known_default().unwrap_or(1080);
+    let Some(host) = url.host_str() else {
+        return Some(format!("'{option}' {proxy} lacks a host"));
+    };
+
+    let addrs = match std::net::ToSocketAddrs::to_socket_addrs(&(host, port)) {
+        Ok(addrs) => addrs.collect::<Vec<std::net::SocketAddr>>(),
+        Err(e) => return Some(format!("'{option}' host {host} can't be resolved: {e}")),
+    };
+    let timeout = std::time::Duration::from_secs(PROXY_PROBE_SECS);
+    if addrs
+        .iter()
+        .any(|addr| std::net::TcpStream::connect_timeout(addr, timeout).is_ok())
+    {
+        None
+    } else {
+        Some(format!("'{option}' {host}:{port} is not reachable"))
+    }
+}
+
+// Validate the configuration as a whole, i.e., the directories, executables, and
+// proxies autotube relies on as well as whether configured values contradict
+// each other, collecting all problems found rather than stopping at the first.
+fn validate_config(worker: &WorkerArgs, following: Option<&FollowingArgs>) -> Vec<ConfigProblem> {
+    let mut problems = Vec::new();
+    let mut fatal = |message: String| {
+        problems.push(ConfigProblem {
+            fatal: true,
+            message,
+        });
+    };
+
+    let video_dir_problem = check_dir("--video-dir", &worker.video_dir);
+    let tmp_dir_problem = check_dir("--tmp-dir", &worker.tmp_dir);
+    let tmp_dir_ok = tmp_dir_problem.is_none();
+    video_dir_problem.into_iter().for_each(&mut fatal);
+    tmp_dir_problem.into_iter().for_each(&mut fatal);
+
+    if let Some(manifest_file) = &worker.manifest_file
+        && let Some(parent) = std::path::Path::new(manifest_file).parent()
+        && !parent.as_os_str().is_empty()
+        && !parent.is_dir()
+    {
+        fatal(format!(
+            "Directory of '--manifest-file' {manifest_file} does not exist"
+        ));
+    }
+    if let Some(images_dir) = following.and_then(|f| f.channel_images_dir.as_deref())
+        && std::path::Path::new(images_dir).exists()
+        && !std::path::Path::new(images_dir).is_dir()
+    {
+        fatal(format!(
+            "'--channel-images-dir' {images_dir} is not a directory"
+        ));
+    }
+    if let Some(cookies_dir) = following.and_then(|f| f.cookies_dir.as_deref())
+        && !std::path::Path::new(cookies_dir).is_dir()
+    {
+        fatal(format!("'--cookies-dir' {cookies_dir} is not a directory"));
+    }
+
+    // The default number of initial downloads needs to be satisfiable from a
+    // channel's RSS feed alone, unless backfilling via the uploads playlist.
+    if let Some(following) = following
+        && usize::from(following.default_download_as_of) > FEED_MAX_VIDEOS
+        && !worker.playlist_backfill
+    {
+        fatal(format!(
+            "Default 'download_as_of' of {} exceeds the {FEED_MAX_VIDEOS} videos listed in RSS feeds, enable '--playlist-backfill' for more",
+            following.default_download_as_of
+        ));
+    }
+
+    // Executables are called from within the temporary directory, so fall back to
+    // the current directory if that one is unusable.
+    let missing = missing_dependencies(if tmp_dir_ok { &worker.tmp_dir } else { "." });
+    if missing.contains(&"yt-dlp") {
+        fatal("No 'yt-dlp' executable found, make sure it is installed".to_string());
+    }
+
+    // Extracting audio is impossible without 'ffmpeg'. Otherwise, some downloads
+    // still succeed without it, and proxies may just not be up yet, so these
+    // problems are reported without keeping autotube from starting.
+    if missing.contains(&"ffmpeg") && worker.extract_audio.is_some() {
+        fatal("No 'ffmpeg' executable found, which '--extract-audio' requires".to_string());
+    } else if missing.contains(&"ffmpeg") {
+        problems.push(ConfigProblem {
+            fatal: false,
+            message: "No 'ffmpeg' executable found, downloads needing formats merged or metadata embedded will fail".to_string(),
+        });
+    }
+    for (option, proxy) in [
+        ("--download-proxy", &worker.download_proxy),
+        ("--rss-proxy", &worker.rss_proxy),
+    ] {
+        if let Some(message) = proxy.as_deref().and_then(|p| check_proxy(option, p)) {
+            problems.push(ConfigProblem {
+                fatal: false,
+                message,
+            });
+        }
+    }
+
+    problems
+}
+
+// Error out early on if autotube can't work as configured, after reporting all
+// problems with the configuration found at once.
+fn check_prerequisites(
+    worker: &WorkerArgs,
+    following: Option<&FollowingArgs>,
+) -> anyhow::Result<()> {
+    let problems = validate_config(worker, following);
+    for problem in &problems {
+        if problem.fatal {
+            event!(Level::ERROR, "Configuration problem: {}", problem.message);
+        } else {
+            event!(Level::WARN, "Configuration problem: {}", problem.message);
+        }
+    }
+
+    let num_fatal = problems.iter().filter(|p| p.fatal).count();
+    if num_fatal > 0 {
+        return Err(anyhow::anyhow!(
+            "Found {num_fatal} fatal configuration problems, see above"
+        ));
+    }
+
+    Ok(())
+}
+
+impl WorkerArgs {
+    // Construct the proxy RSS feeds and channel webpages are fetched through, if
+    // configured.
+    fn rss_proxy(&self) -> anyhow::Result<Option<reqwest::Proxy>> {
+        Ok(self
+            .rss_proxy
+            .as_deref()
+            .map(reqwest::Proxy::all)
+            .transpose()?)
+    }
+
+    // Collect the additional HTTP request headers to send along with every
+    // request for an RSS feed or channel webpage. Later headers of the same name
+    // are sent in addition to earlier ones.
+    fn http_headers(&self) -> reqwest::header::HeaderMap {
+        let mut headers = reqwest::header::HeaderMap::new();
+        for (name, value) in &self.http_header {
+            headers.append(name.clone(), value.clone());
+        }
+        headers
+    }
+
+    // Construct the fetcher of RSS feeds as configured.
+    fn feed_fetcher(&self) -> anyhow::Result<FeedFetcher> {
+        Ok(FeedFetcher::new(
+            self.user_agent.clone(),
+            self.rss_proxy()?,
+            self.http_headers(),
+            self.feed_attempts,
+            usize::from(self.feed_max_per_host),
+        ))
+    }
+
+    // Construct the HTTP client channel webpages are fetched with as configured.
+    fn http_client(&self) -> anyhow::Result<reqwest::Client> {
+        let mut builder = reqwest::Client::builder()
+            .user_agent(&self.user_agent)
+            .default_headers(self.http_headers());
+        if let Some(proxy) = self.rss_proxy()? {
+            builder = builder.proxy(proxy);
+        }
+        Ok(builder.build()?)
+    }
+
+    // Collect the settings of the worker executing jobs as configured.
+    fn into_worker_config(self) -> WorkerConfig {
+        WorkerConfig {
+            video_dir: self.video_dir,
+            tmp_dir: self.tmp_dir,
+            audio_lang: self.audio_lang,
+            extract_audio: self.extract_audio,
+            audio_quality: self.audio_quality,
+            retry_days: self.retry_days,
+            cookies_file: self.cookies_file,
+            netrc_location: self.netrc_location,
+            playlist_backfill: self.playlist_backfill,
+            user_agent: self.user_agent,
+            download_proxy: self.download_proxy,
+            compute_checksums: self.compute_checksums,
+            restrict_filenames: self.restrict_filenames,
+            max_filename_bytes: usize::from(self.max_filename_bytes),
+            video_file_mode: self.video_file_mode,
+            merge_output_format: self.merge_output_format,
+            extractor_args: self.extractor_arguments,
+            parse_metadata: self.parse_metadata,
+            replace_in_metadata: self.replace_in_metadata,
+            format_sort: self.format_sort,
+            format_constraints: FormatConstraints {
+                video_codecs: self.accepted_video_codecs,
+                audio_codecs: self.accepted_audio_codecs,
+                container: self.accepted_container,
+            },
+            ondemand_filename_template: self.ondemand_filename_template,
+            channel_filename_template: self.channel_filename_template,
+            concurrent_fragments: self.concurrent_fragments,
+            max_per_check: self.max_per_check.map(usize::from),
+            max_downloads_per_day: self.max_downloads_per_day,
+            download_day_offset: self.download_day_utc_offset,
+            manifest_file: self.manifest_file,
+            follow_order: self.follow_order,
+            check_order: self.check_order,
+            last_checked_init: self.last_checked_init,
+            on_collision: self.on_collision,
+            on_postprocessing_error: self.on_postprocessing_error,
+            geo_bypass: self.geo_bypass,
+            backfill_batch_size: usize::from(self.backfill_batch_size),
+            dependency_policy: self.on_missing_dependency,
+        }
+    }
+
+    // Construct the state of the worker executing jobs as configured, sharing
+    // the supplied handles with whoever else needs them.
+    fn into_worker_state(
+        self,
+        submit_job: &JobQueue,
+        db_pool: &sqlx::AnyPool,
+        fetcher: FeedFetcher,
+        active_downloads: &ActiveDownloads,
+        heartbeat: &Heartbeat,
+    ) -> anyhow::Result<WorkerState> {
+        WorkerState::new(
+            submit_job,
+            db_pool,
+            fetcher,
+            active_downloads,
+            heartbeat,
+            self.into_worker_config(),
+        )
+    }
+}
+
+// Insert the `serve` subcommand into the supplied command line arguments if
+// they don't start with a subcommand, so that autotube keeps running as a server
+// when invoked without one. Leading verbosity flags are skipped over, as they
+// apply to all subcommands. Requests for help or the version are left alone to
+// reach the top-level command listing all subcommands.
+fn args_with_default_subcommand(
+    args: impl IntoIterator<Item = std::ffi::OsString>,
+) -> Vec<std::ffi::OsString> {
+    let mut args = args.into_iter().collect::<Vec<std::ffi::OsString>>();
+    let is_verbosity_flag = |a: &std::ffi::OsString| {
+        a.to_str().is_some_and(|a| {
+            matches!(a, "-q" | "--quiet" | "--verbose")
+                || a.strip_prefix('-')
+                    .is_some_and(|v| !v.is_empty() && v.chars().all(|c| c == 'v'))
+        })
+    };
+    let first = args
+        .iter()
+        .skip(1)
+        .position(|a| !is_verbosity_flag(a))
+        .map_or(args.len(), |p| p.saturating_add(1));
+    let names_subcommand = args.get(first).and_then(|a| a.to_str()).is_some_and(|a| {
+        matches!(a, "help" | "-h" | "--help" | "-V" | "--version")
+            || Cli::command().find_subcommand(a).is_some()
+    });
+    if !names_subcommand {
+        args.insert(first.max(args.len().min(1)), "serve".into());
+    }
+    args
+}
+
+// Map the verbosity flags to the level of messages to log, unless 'RUST_LOG'
+// configures logging differently.
+fn default_log_level(quiet: bool, verbose: u8) -> &'static str {
+    match (quiet, verbose) {
+        (true, _) => "warn",
+        (false, 0) => "info",
+        (false, 1) => "debug",
+        (false, _) => "trace",
+    }
+}
+
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     // Parse CLI and ENV arguments.
-    let args = Args::parse();
+    let cli = Cli::parse_from(args_with_default_subcommand(std::env::args_os()));
 
     // Configure our tracing/logger.
     let format_layer = tracing_subscriber::fmt::layer()
         .with_file(true)
         .with_line_number(true)
         .compact();
-    let filter_layer = tracing_subscriber::EnvFilter::try_from_default_env()
-        .or_else(|_| tracing_subscriber::EnvFilter::try_new("info"))?;
+    let filter_layer = tracing_subscriber::EnvFilter::try_from_default_env().or_else(|_| {
+        tracing_subscriber::EnvFilter::try_new(default_log_level(cli.quiet, cli.verbose))
+    })?;
     tracing_subscriber::registry()
         .with(filter_layer)
         .with(format_layer)
         .init();
     event!(Level::DEBUG, "Launching...");
 
-    // Error out early on if `yt-dlp` can't be called from autotube.
-    if std::process::Command::new("yt-dlp")
-        .env_clear()
-        .current_dir(&args.tmp_dir)
-        .arg("--version")
-        .output()
-        .is_err()
-    {
-        return Err(anyhow::anyhow!(
-            "No 'yt-dlp' executable found, make sure it is installed"
-        ));
+    match cli.command {
+        Command::Serve(args) => serve(args).await,
+        Command::Follow(args) => cli::follow(args).await,
+        Command::Check(args) => cli::check(args).await,
+        Command::List(args) => cli::list(args).await,
+        Command::Download(args) => cli::download(args).await,
     }
+}
+
+// Run the HTTP server handling client requests along with the background tasks
+// triggering channel checks and executing jobs, until asked to shut down.
+async fn serve(args: ServeArgs) -> anyhow::Result<()> {
+    check_prerequisites(&args.worker, Some(&args.following))?;
 
-    // Initialize a connection to the SQLite database and also create the primary
-    // table if it doesn't exist.
-    let db_pool = db::init_db().await?;
+    // Also error out early on if any of the configured CORS origins is invalid.
+    let cors_layer = build_cors_layer(&args.cors_origin)?;
+
+    // Initialize a connection to the configured database and also create the
+    // primary table if it doesn't exist.
+    let db_pool = args.db.init_db().await?;
+    let read_pool = args.db.read_pool(&db_pool).await?;
 
     // Prepare ctrl+c signal handling: Spawn a background task waiting for ctrl+c
     // being pressend to then drop the sender side of a broadcast channel to which
@@ -90,44 +1097,77 @@ async fn main() -> anyhow::Result<()> {
     // sender getting dropped, they initiate shutdown.
     let (send_shutdown, _) = tokio::sync::broadcast::channel::<()>(1);
 
-    // Prepare an MPSC channel pair with a decent buffer size for HTTP handlers to
-    // submit jobs to a (blocking) background process to execute.
-    let (submit_job, recv_job) = tokio::sync::mpsc::channel::<Job>(256);
+    // Prepare a job queue with a decent buffer size for HTTP handlers to submit
+    // jobs to a (blocking) background process to execute.
+    let (submit_job, recv_job) = JobQueue::new(&db_pool, 256);
+
+    // Keep this instance recorded as alive, so that other instances sharing the
+    // database don't remove its mirrored queue entries, while removing the ones of
+    // instances that crashed.
+    let keep_alive_handle =
+        tokio::task::spawn(submit_job.clone().keep_alive(send_shutdown.subscribe()));
+
+    // HTTP handlers report on the downloads the worker is currently running and
+    // on whether the worker is still taking jobs off the queue.
+    let active_downloads = ActiveDownloads::default();
+    let heartbeat = Heartbeat::new();
+
+    // Both HTTP handlers and workers fetch RSS feeds of channels the same way.
+    let fetcher = args.worker.feed_fetcher()?;
+    let unparsed_feeds = fetcher.unparsed_feeds();
 
     // The job sender end goes into the state struct that will be passed to each
     // HTTP request handler axum will spawn.
-    let handler_state = HTTPHandlerState::new(&submit_job, &db_pool);
+    let handler_state = HTTPHandlerState::new(
+        &submit_job,
+        &db_pool,
+        &read_pool,
+        args.worker.http_client()?,
+        &fetcher,
+        &active_downloads,
+        &heartbeat,
+        args.stall_secs,
+        args.following.default_download_as_of,
+        args.following.channel_images_dir,
+        args.following.cookies_dir,
+        args.worker.page_attempts,
+        args.worker.video_dir.clone(),
+        args.worker.tmp_dir.clone(),
+        args.worker.backfill_max_videos,
+    );
 
     // Run the background task triggering the check for new videos on any of the
     // followed channels and also provide it access to the job queue and the
     // database.
-    let trigger_state = TriggerState::new(&submit_job, &db_pool);
+    let trigger_state = TriggerState::new(
+        &submit_job,
+        &db_pool,
+        args.check_spread,
+        args.check_jitter,
+        args.check_spread_for,
+        args.check_jitter_for,
+        args.check_on_startup,
+        args.startup_jitter_secs,
+        &active_downloads,
+        args.throttle_checks_above,
+    );
+    let checks_triggered = trigger_state.checks_triggered();
     let trigger_shutdown = send_shutdown.subscribe();
     let trigger_handle = tokio::task::spawn(trigger_state.run(trigger_shutdown));
 
-    let worker_state = WorkerState::new(&submit_job, &db_pool, args.video_dir, args.tmp_dir)?;
+    let worker_state = args.worker.into_worker_state(
+        &submit_job,
+        &db_pool,
+        fetcher,
+        &active_downloads,
+        &heartbeat,
+    )?;
+    let session_stats = worker_state.stats();
     let worker_shutdown = send_shutdown.subscribe();
     let worker_handle = tokio::task::spawn(worker_state.run(recv_job, worker_shutdown));
 
-    // Build HTTP router to handle incoming client requests. Note that we assume to
-    // be running behind a security perimeter (e.g., WireGuard), so that
-    // authentication is not a concern for us.
-    let router = axum::Router::new()
-        .without_v07_checks()
-        .route(
-            "/downloads/ondemand",
-            axum::routing::post(post_downloads_ondemand),
-        )
-        .route(
-            "/channels/follow",
-            axum::routing::post(post_channels_follow),
-        )
-        .layer(
-            tower_http::validate_request::ValidateRequestHeaderLayer::bearer(
-                args.bearer_token.as_str(),
-            ),
-        )
-        .with_state(handler_state);
+    // Build HTTP router to handle incoming client requests.
+    let router = build_router(handler_state, &args.bearer_token, cors_layer);
 
     // Spawn a tokio TCP listener on the configured listening IP and port, and pass
     // it off to axum to handle the configured HTTP routes.
@@ -140,16 +1180,186 @@ async fn main() -> anyhow::Result<()> {
         args.listen_port
     );
 
-    // Block on HTTP handler, returning upon shutdown.
+    // Block on HTTP handler, returning upon shutdown. Shutting down must not take
+    // longer than configured, e.g., due to a download stuck in 'yt-dlp'.
+    let deadline = ShutdownDeadline {
+        timeout_secs: args.shutdown_timeout_secs,
+        tasks: vec![
+            ("worker", worker_handle.abort_handle()),
+            ("trigger", trigger_handle.abort_handle()),
+        ],
+        active_downloads,
+    };
     axum::serve(listener, router)
-        .with_graceful_shutdown(shutdown_upon_signal(send_shutdown))
+        .with_graceful_shutdown(shutdown_upon_signal(send_shutdown, deadline))
         .await?;
 
     // Once HTTP handler completed, also wait for background tasks and database
-    // connections to exit.
+    // connections to exit. Jobs still waiting in the queue are lost, so their
+    // mirrored entries go too, and backfills still running are interrupted.
     let _ = worker_handle.await;
     trigger_handle.await?;
+    keep_alive_handle.await?;
+    submit_job.forget_all().await;
+    if let Err(e) = db::interrupt_backfills(&db_pool, submit_job.instance()).await {
+        event!(Level::WARN, "{e}");
+    }
+    read_pool.close().await;
     db_pool.close().await;
 
+    // Summarize the activity of this session. Jobs still running in the background
+    // when shutting down count as unfinished.
+    event!(
+        Level::INFO,
+        "Session summary: {session_stats}, {} channel checks triggered, {} RSS feeds with unparsed entries",
+        checks_triggered.load(std::sync::atomic::Ordering::Relaxed),
+        unparsed_feeds.load(std::sync::atomic::Ordering::Relaxed),
+    );
+
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_args_with_default_subcommand() {
+        let parse = |args: &[&str]| {
+            Cli::try_parse_from(args_with_default_subcommand(
+                args.iter().map(std::ffi::OsString::from),
+            ))
+        };
+
+        // Invocations without a subcommand keep starting the server.
+        let Ok(cli) = parse(&[
+            "autotube",
+            "--bearer-token",
+            "secret",
+            "--video-dir",
+            "/videos",
+            "--tmp-dir",
+            "/tmp",
+        ]) else {
+            panic!("Failed to parse arguments without subcommand");
+        };
+        assert!(matches!(cli.command, Command::Serve(args) if args.bearer_token == "secret"));
+        assert!(parse(&["autotube", "--video-dir", "/videos", "--tmp-dir", "/tmp"]).is_err());
+
+        assert!(parse(&["autotube", "list"]).is_ok_and(|c| matches!(c.command, Command::List(_))));
+        assert!(
+            parse(&[
+                "autotube",
+                "check",
+                "--video-dir",
+                "/videos",
+                "--tmp-dir",
+                "/tmp"
+            ])
+            .is_ok_and(|c| matches!(c.command, Command::Check(_)))
+        );
+        assert!(
+            parse(&[
+                "autotube",
+                "download",
+                "--video-dir",
+                "/videos",
+                "--tmp-dir",
+                "/tmp"
+            ])
+            .is_err()
+        );
+
+        // Verbosity flags apply to all subcommands, including the default one.
+        assert!(
+            parse(&["autotube", "-vv", "list"])
+                .is_ok_and(|c| c.verbose == 2 && matches!(c.command, Command::List(_)))
+        );
+        assert!(
+            parse(&["autotube", "list", "--quiet"])
+                .is_ok_and(|c| c.quiet && matches!(c.command, Command::List(_)))
+        );
+        assert!(
+            parse(&[
+                "autotube",
+                "-v",
+                "--bearer-token",
+                "secret",
+                "--video-dir",
+                "/videos",
+                "--tmp-dir",
+                "/tmp",
+            ])
+            .is_ok_and(|c| c.verbose == 1 && matches!(c.command, Command::Serve(_)))
+        );
+        assert!(parse(&["autotube", "-q", "-v", "list"]).is_err());
+    }
+
+    #[test]
+    fn test_default_log_level() {
+        assert_eq!(default_log_level(false, 0), "info");
+        assert_eq!(default_log_level(true, 0), "warn");
+        assert_eq!(default_log_level(false, 1), "debug");
+        assert_eq!(default_log_level(false, 3), "trace");
+    }
+
+    #[test]
+    fn test_bearer_token_matches() {
+        let headers = |value: &str| {
+            let mut headers = axum::http::HeaderMap::new();
+            if let Ok(v) = axum::http::HeaderValue::from_str(value) {
+                headers.insert(axum::http::header::AUTHORIZATION, v);
+            }
+            headers
+        };
+
+        assert!(bearer_token_matches(&headers("Bearer secret"), "secret"));
+        assert!(!bearer_token_matches(&headers("Bearer secreT"), "secret"));
+        assert!(!bearer_token_matches(&headers("Bearer secret2"), "secret"));
+        assert!(!bearer_token_matches(&headers("Bearer "), "secret"));
+        assert!(!bearer_token_matches(&headers("Basic secret"), "secret"));
+        assert!(!bearer_token_matches(
+            &axum::http::HeaderMap::new(),
+            "secret"
+        ));
+
+        assert!(constant_time_eq(b"", b""));
+        assert!(!constant_time_eq(b"abc", b"abd"));
+        assert!(!constant_time_eq(b"abc", b"ab"));
+    }
+
+    #[test]
+    fn test_validate_config() {
+        let Ok(cli) = Cli::try_parse_from([
+            "autotube",
+            "serve",
+            "--bearer-token",
+            "secret",
+            "--video-dir",
+            "/nonexistent/videos",
+            "--tmp-dir",
+            "/nonexistent/tmp",
+            "--default-download-as-of",
+            "20",
+            "--download-proxy",
+            "http://127.0.0.1:1",
+        ]) else {
+            panic!("Failed to parse arguments");
+        };
+        let Command::Serve(args) = cli.command else {
+            panic!("Parsed unexpected subcommand");
+        };
+
+        // All problems are reported at once, not just the first one.
+        let problems = validate_config(&args.worker, Some(&args.following));
+        let has_problem = |fatal: bool, part: &str| {
+            problems
+                .iter()
+                .any(|p| p.fatal == fatal && p.message.contains(part))
+        };
+        assert!(has_problem(true, "'--video-dir' /nonexistent/videos"));
+        assert!(has_problem(true, "'--tmp-dir' /nonexistent/tmp"));
+        assert!(has_problem(true, "Default 'download_as_of' of 20"));
+        assert!(has_problem(false, "'--download-proxy' 127.0.0.1:1"));
+    }
+}
@@ -0,0 +1,739 @@
+use tracing::{Level, event};
+
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+/// The kinds of URLs clients submit to autotube.
+pub(crate) enum UrlKind {
+    Video,
+    Channel,
+}
+
+impl std::fmt::Display for UrlKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
+        match self {
+            UrlKind::Video => write!(f, "video"),
+            UrlKind::Channel => write!(f, "channel"),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+/// A URL that passed validation by the validator of its platform, in the
+/// canonical form autotube works with. For channels, also carries the URL of
+/// the RSS feed that is consulted for new videos and, if found, the URLs of
+/// the channel's avatar and banner images.
+pub(crate) struct ValidatedUrl {
+    pub(crate) url: String,
+    pub(crate) rss_url: Option<String>,
+    pub(crate) avatar_url: Option<String>,
+    pub(crate) banner_url: Option<String>,
+}
+
+// Future returned by `PlatformValidator::validate`. It is boxed so that
+// validators of different platforms can be looked up dynamically by domain.
+type ValidateFuture<'a> =
+    std::pin::Pin<Box<dyn Future<Output = anyhow::Result<ValidatedUrl>> + Send + 'a>>;
+
+/// Validation of URLs specific to one video platform. Adding support for a new
+/// platform means implementing this trait and registering the implementation
+/// for the platform's domains in `PLATFORMS`.
+pub(crate) trait PlatformValidator: Sync {
+    /// Verify that the supplied URL of the supplied kind is valid on this
+    /// platform and return it in canonical form. Webpages fetched as part of
+    /// the validation are attempted at most `page_attempts` times.
+    fn validate<'a>(
+        &'a self,
+        http_client: &'a reqwest::Client,
+        page_attempts: u8,
+        kind: UrlKind,
+        url: &'a str,
+    ) -> ValidateFuture<'a>;
+}
+
+// Validates `YouTube` video and channel URLs.
+struct YouTubeValidator;
+
+impl PlatformValidator for YouTubeValidator {
+    fn validate<'a>(
+        &'a self,
+        http_client: &'a reqwest::Client,
+        page_attempts: u8,
+        kind: UrlKind,
+        url: &'a str,
+    ) -> ValidateFuture<'a> {
+        Box::pin(validate_youtube_url(http_client, page_attempts, kind, url))
+    }
+}
+
+// Number of milliseconds to wait before the second attempt at fetching a channel
+// webpage during validation, doubling with each further attempt.
+const CHANNEL_PAGE_BACKOFF_MILLIS: u64 = 500;
+
+// Registry of the validators of all supported platforms, keyed by the domains
+// (without 'www.') their URLs are served from.
+const PLATFORMS: &[(&str, &dyn PlatformValidator)] = &[("youtube.com", &YouTubeValidator)];
+
+// Extract the domain from the supplied URL, ignoring the scheme, a leading
+// 'www.', and a port, and look up the validator registered for it.
+fn platform_for_url(url: &str) -> Option<&'static dyn PlatformValidator> {
+    let url = url.trim_start_matches("https://");
+    let url = url.trim_start_matches("http://");
+    let url = url.trim_start_matches("www.");
+
+    let host = url.split(['/', '?', '#']).next()?;
+    let domain = host.split(':').next()?.to_lowercase();
+
+    PLATFORMS
+        .iter()
+        .find(|(d, _)| *d == domain)
+        .map(|(_, validator)| *validator)
+}
+
+// Verifies the supplied URL by dispatching to the validator of the platform
+// registered for the URL's domain. URLs of all other domains are rejected.
+pub(crate) async fn validate_url(
+    http_client: &reqwest::Client,
+    page_attempts: u8,
+    kind: UrlKind,
+    url: &str,
+) -> anyhow::Result<ValidatedUrl> {
+    if url.is_empty() {
+        return Err(anyhow::anyhow!(format!("Empty {kind} URL")));
+    }
+
+    let Some(validator) = platform_for_url(url) else {
+        event!(Level::DEBUG, "Unsupported or invalid {kind} URL: {url}");
+        return Err(anyhow::anyhow!(format!(
+            "Unsupported or invalid {kind} URL"
+        )));
+    };
+
+    validator
+        .validate(http_client, page_attempts, kind, url)
+        .await
+}
+
+// Verifies that everthing after 'youtube.com/watch?' in a `YouTube` video URL
+// is as required, meaning that we need to find the video ID in the query
+// parameters. Only used as part of validate_youtube_url, which means that we
+// don't check for 'youtube.com/watch?' at the front of the URL string again.
+// Returns the final, validated, full `YouTube` URL to the video.
+fn validate_youtube_video_url(url: &str) -> anyhow::Result<String> {
+    let url_parts = &url[18..].split('&').collect::<Vec<&str>>();
+
+    let Some(video_id) = url_parts
+        .iter()
+        .find(|&&p| p.len() == 13 && p.starts_with("v="))
+    else {
+        event!(
+            Level::DEBUG,
+            "Video ID parameter missing from or incorrect in YouTube URL: {url}"
+        );
+        return Err(anyhow::anyhow!(
+            "Video ID parameter missing from or incorrect in YouTube URL"
+        ));
+    };
+
+    Ok(format!("https://www.youtube.com/watch?{video_id}"))
+}
+
+// Finds the channel ID of a `YouTube` channel without relying on the RSS feed
+// link element in its webpage. Channel URLs of the form '/channel/UC...' carry
+// the ID themselves, otherwise the webpage is searched for the channel's
+// canonical or 'og:url' URL or for the 'externalId' and 'channelId' fields in
+// its embedded JSON data, in that order.
+fn fallback_channel_id(channel_url: &str, channel_webpage: &str) -> Option<String> {
+    let channel_id_re = regex::Regex::new(r"^UC[0-9A-Za-z_-]{22}$").ok()?;
+    if let Some(channel_id) = channel_url
+        .strip_prefix("https://www.youtube.com/channel/")
+        .filter(|id| channel_id_re.is_match(id))
+    {
+        return Some(channel_id.to_string());
+    }
+
+    let webpage_re = regex::Regex::new(
+        r#"(?:youtube\.com/channel/|"externalId"\s*:\s*"|"channelId"\s*:\s*")(UC[0-9A-Za-z_-]{22})(?:["'/?]|$)"#,
+    )
+    .ok()?;
+    webpage_re
+        .captures(channel_webpage)
+        .and_then(|c| c.get(1))
+        .map(|m| m.as_str().to_string())
+}
+
+// Extracts the channel ID from the RSS feed link element embedded in a `YouTube`
+// channel webpage and returns the RSS feed URL constructed from it. Manual tests
+// have shown that this element is present in the DOM of any `YouTube` channel
+// webpage. Rather than relying on the exact markup of the element, we look for
+// any link element announcing an RSS feed and only then extract the channel ID
+// from its `href` attribute, which needs to be 24 characters starting with 'UC'.
+fn extract_channel_rss_url(channel_webpage: &str) -> anyhow::Result<String> {
+    let rss_link_re =
+        regex::Regex::new(r#"<link\s[^>]*?type\s*=\s*["']application/rss\+xml["'][^>]*>"#)?;
+    let channel_id_re = regex::Regex::new(
+        r#"href\s*=\s*["']https://www\.youtube\.com/feeds/videos\.xml\?channel_id=(UC[0-9A-Za-z_-]{22})["']"#,
+    )?;
+
+    let Some(channel_id) = rss_link_re
+        .find_iter(channel_webpage)
+        .find_map(|link| channel_id_re.captures(link.as_str()))
+        .and_then(|c| c.get(1))
+    else {
+        return Err(anyhow::anyhow!(
+            "Didn't find channel ID in YouTube channel webpage"
+        ));
+    };
+
+    Ok(format!(
+        "https://www.youtube.com/feeds/videos.xml?channel_id={}",
+        channel_id.as_str()
+    ))
+}
+
+// Extracts the URLs of the avatar and banner images of a `YouTube` channel from
+// its webpage. The avatar is announced via the 'og:image' meta element, with the
+// avatar in the channel JSON embedded in the webpage as a fallback, while the
+// banner is only found in the channel JSON. Either is `None` if not found.
+fn extract_channel_image_urls(channel_webpage: &str) -> (Option<String>, Option<String>) {
+    // URLs in the channel JSON escape some characters, URLs in attributes of HTML
+    // elements escape ampersands.
+    let unescape = |url: &str| {
+        url.replace("\\u0026", "&")
+            .replace("\\/", "/")
+            .replace("&amp;", "&")
+    };
+    let capture = |re: &str, haystack: &str| {
+        regex::Regex::new(re)
+            .ok()?
+            .captures(haystack)
+            .and_then(|c| c.get(1))
+            .map(|m| unescape(m.as_str()))
+            .filter(|url| url.starts_with("https://"))
+    };
+
+    let og_image = regex::Regex::new(r#"<meta\s[^>]*?property\s*=\s*["']og:image["'][^>]*>"#)
+        .ok()
+        .and_then(|re| re.find(channel_webpage))
+        .and_then(|meta| capture(r#"content\s*=\s*["']([^"']+)["']"#, meta.as_str()));
+    let avatar_url = og_image.or_else(|| {
+        capture(
+            r#""avatar"\s*:\s*\{\s*"thumbnails"\s*:\s*\[\s*\{\s*"url"\s*:\s*"([^"]+)""#,
+            channel_webpage,
+        )
+    });
+    let banner_url = capture(
+        r#""banner"\s*:\s*\{.{0,300}?"url"\s*:\s*"([^"]+)""#,
+        channel_webpage,
+    );
+
+    (avatar_url, banner_url)
+}
+
+// Returns the channel ID contained in the RSS feed URL of a `YouTube` channel,
+// which is the stable identifier of a channel independent of the URL form that
+// was used to refer to it.
+pub(crate) fn channel_id_from_rss_url(rss_url: &str) -> Option<&str> {
+    rss_url
+        .split_once("channel_id=")
+        .map(|(_, channel_id)| channel_id)
+        .filter(|channel_id| channel_id.len() == 24 && channel_id.starts_with("UC"))
+}
+
+// Cleans the supplied `YouTube` channel URL by stripping everything following
+// the channel handle (in '/@handle' URLs) or the channel ID (in '/channel/UC...'
+// URLs) and returns it in the canonical form it is stored in the database as.
+// Handles are case-insensitive and thus lowercased, while channel IDs are not.
+// Does not verify that the channel actually exists.
+pub(crate) fn canonical_youtube_channel_url(url: &str) -> Option<String> {
+    let url = url.trim_start_matches("https://");
+    let url = url.trim_start_matches("http://");
+    let url = url.trim_start_matches("www.");
+
+    if let Some(channel_part) = url.strip_prefix("youtube.com/channel/") {
+        let channel_id = match channel_part.split_once(['/', '?']) {
+            Some((id, _)) => id,
+            None => channel_part,
+        };
+
+        if channel_id.len() != 24
+            || !channel_id.starts_with("UC")
+            || !channel_id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        {
+            return None;
+        }
+
+        return Some(format!("https://www.youtube.com/channel/{channel_id}"));
+    }
+
+    let channel_part = url.strip_prefix("youtube.com/@")?;
+    let channel_name = match channel_part.split_once('/') {
+        Some((name, _)) => name,
+        None => channel_part,
+    };
+
+    Some(format!("https://www.youtube.com/@{channel_name}").to_lowercase())
+}
+
+// Fetch the webpage of the `YouTube` channel at `channel_url` once, telling apart
+// failures worth retrying (connection problems, server errors, rate limiting)
+// from definite ones (e.g., '404 Not Found' for channels that don't exist).
+async fn fetch_channel_webpage_once(
+    http_client: &reqwest::Client,
+    channel_url: &str,
+) -> Result<String, (bool, anyhow::Error)> {
+    let Ok(resp) = http_client.get(channel_url).send().await else {
+        return Err((
+            true,
+            anyhow::anyhow!("Failed to connect to supplied YouTube channel URL via HTTP"),
+        ));
+    };
+
+    let status = resp.status();
+    if status != reqwest::StatusCode::OK {
+        let retry = status.is_server_error() || status == reqwest::StatusCode::TOO_MANY_REQUESTS;
+        return Err((
+            retry,
+            anyhow::anyhow!("Supplied YouTube channel URL did not return 200 OK"),
+        ));
+    }
+
+    resp.text().await.map_err(|_| {
+        (
+            true,
+            anyhow::anyhow!("Unable to obtain webpage content for supplied YouTube channel URL"),
+        )
+    })
+}
+
+// Fetch the webpage of the `YouTube` channel at `channel_url`, attempting it at
+// most `page_attempts` times with a doubling backoff in between, so that
+// transient network issues don't reject valid channels.
+async fn fetch_channel_webpage(
+    http_client: &reqwest::Client,
+    page_attempts: u8,
+    channel_url: &str,
+) -> anyhow::Result<String> {
+    let mut attempt = 1;
+    let mut backoff_millis = CHANNEL_PAGE_BACKOFF_MILLIS;
+    loop {
+        match fetch_channel_webpage_once(http_client, channel_url).await {
+            Ok(channel_webpage) => return Ok(channel_webpage),
+            Err((true, e)) if attempt < page_attempts => {
+                event!(
+                    Level::DEBUG,
+                    "Attempt {attempt} of {page_attempts} at fetching channel webpage {channel_url} failed, retrying in {backoff_millis} ms: {e}"
+                );
+                tokio::time::sleep(std::time::Duration::from_millis(backoff_millis)).await;
+                attempt = attempt.saturating_add(1);
+                backoff_millis = backoff_millis.saturating_mul(2);
+            }
+            Err((_, e)) => {
+                event!(Level::DEBUG, "{e}: {channel_url}");
+                return Err(e);
+            }
+        }
+    }
+}
+
+// Verifies that the submitted `YouTube` channel URL indeed links to an existing
+// channel by first cleaning the URL and then making an HTTP GET request to see
+// if we get a 200 OK response. If successful, extracts the RSS feed URL
+// embedded on the YouTube channel webpage. Returns the final, validated, full
+// `YouTube` URL to the channel along with the extracted RSS feed URL and the
+// URLs of the channel's images, if found.
+async fn validate_youtube_channel_url(
+    http_client: &reqwest::Client,
+    page_attempts: u8,
+    url: &str,
+) -> anyhow::Result<ValidatedUrl> {
+    let Some(channel_url) = canonical_youtube_channel_url(url) else {
+        event!(Level::DEBUG, "Unsupported or invalid channel URL: {url}");
+        return Err(anyhow::anyhow!("Unsupported or invalid channel URL"));
+    };
+
+    let channel_webpage = fetch_channel_webpage(http_client, page_attempts, &channel_url).await?;
+
+    // Prefer the RSS feed link element announced by the webpage. Should the markup
+    // of the webpage have changed, fall back to constructing the RSS feed URL from
+    // the channel ID found by other means.
+    let rss_url = if let Ok(rss_url) = extract_channel_rss_url(&channel_webpage) {
+        rss_url
+    } else {
+        let Some(channel_id) = fallback_channel_id(&channel_url, &channel_webpage) else {
+            event!(
+                Level::DEBUG,
+                "Didn't find channel ID in YouTube channel webpage: {channel_url}"
+            );
+            return Err(anyhow::anyhow!(
+                "Didn't find channel ID in YouTube channel webpage"
+            ));
+        };
+        event!(
+            Level::DEBUG,
+            "No RSS feed link element in YouTube channel webpage {channel_url}, constructed RSS feed URL from channel ID {channel_id}"
+        );
+        format!("https://www.youtube.com/feeds/videos.xml?channel_id={channel_id}")
+    };
+
+    let (avatar_url, banner_url) = extract_channel_image_urls(&channel_webpage);
+
+    Ok(ValidatedUrl {
+        url: channel_url,
+        rss_url: Some(rss_url),
+        avatar_url,
+        banner_url,
+    })
+}
+
+// Verifies that the supplied URL is a valid YouTube URL (either pointing to a
+// video or a channel) and rejects all others. If successful, returns the
+// cleaned and canonicalized version of the input URL.
+async fn validate_youtube_url(
+    http_client: &reqwest::Client,
+    page_attempts: u8,
+    kind: UrlKind,
+    url: &str,
+) -> anyhow::Result<ValidatedUrl> {
+    if url.is_empty() {
+        return Err(anyhow::anyhow!(format!("Empty YouTube {kind} URL")));
+    }
+
+    let url = url.trim_start_matches("https://");
+    let url = url.trim_start_matches("http://");
+    let url = url.trim_start_matches("www.");
+
+    match kind {
+        UrlKind::Video => {
+            if url.starts_with("youtube.com/watch?") {
+                let valid_url = validate_youtube_video_url(url)?;
+                Ok(ValidatedUrl {
+                    url: valid_url,
+                    rss_url: None,
+                    avatar_url: None,
+                    banner_url: None,
+                })
+            } else {
+                event!(Level::DEBUG, "Unsupported or invalid video URL: {url}");
+                Err(anyhow::anyhow!("Unsupported or invalid video URL"))
+            }
+        }
+        UrlKind::Channel => {
+            if url.starts_with("youtube.com/@") || url.starts_with("youtube.com/channel/") {
+                validate_youtube_channel_url(http_client, page_attempts, url).await
+            } else {
+                event!(Level::DEBUG, "Unsupported or invalid channel URL: {url}");
+                Err(anyhow::anyhow!("Unsupported or invalid channel URL"))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_validate_video_urls() {
+        // Below URL inputs to validate_youtube_url() should all produce an Error
+        // result with the associated error message.
+        let should_error = [
+            ("", "Empty YouTube video URL"),
+            ("abc", "Unsupported or invalid video URL"),
+            ("http://vimeo.com", "Unsupported or invalid video URL"),
+            ("https://www.google.com", "Unsupported or invalid video URL"),
+            (
+                "youtube.org/watch?v=0123456789a",
+                "Unsupported or invalid video URL",
+            ),
+            (
+                "https://www.youtube.com/watch?v=0123456789",
+                "Video ID parameter missing from or incorrect in YouTube URL",
+            ),
+            (
+                "https://www.youtube.com/watch?v=0123456789ab",
+                "Video ID parameter missing from or incorrect in YouTube URL",
+            ),
+            (
+                "https://www.youtube.com/watch?k=0123456789a",
+                "Video ID parameter missing from or incorrect in YouTube URL",
+            ),
+            (
+                "https://www.youtube.com/watch?v=0123456789&list=abcdefghijklmnopqrstuvwxyzeRgBdnBM",
+                "Video ID parameter missing from or incorrect in YouTube URL",
+            ),
+        ];
+
+        for (url, exp_err) in &should_error {
+            assert!(
+                validate_youtube_url(&reqwest::Client::new(), 1, UrlKind::Video, url)
+                    .await
+                    .is_err_and(|e| {
+                        let ret_err = e.to_string();
+                        if ret_err == *exp_err {
+                            true
+                        } else {
+                            println!("{url}: Expected '{exp_err}', but got '{ret_err}'");
+                            false
+                        }
+                    })
+            );
+        }
+
+        // Below URL inputs to validate_youtube_url() should all produce an Ok result
+        // with the associated valid URL returned.
+        let should_succeed = [
+            (
+                "youtube.com/watch?v=0123456789a",
+                "https://www.youtube.com/watch?v=0123456789a",
+            ),
+            (
+                "www.youtube.com/watch?v=0123456789a",
+                "https://www.youtube.com/watch?v=0123456789a",
+            ),
+            (
+                "http://youtube.com/watch?v=0123456789a",
+                "https://www.youtube.com/watch?v=0123456789a",
+            ),
+            (
+                "http://www.youtube.com/watch?v=0123456789a",
+                "https://www.youtube.com/watch?v=0123456789a",
+            ),
+            (
+                "https://www.youtube.com/watch?v=0123456789a",
+                "https://www.youtube.com/watch?v=0123456789a",
+            ),
+            (
+                "https://www.youtube.com/watch?v=0123456789a&",
+                "https://www.youtube.com/watch?v=0123456789a",
+            ),
+            (
+                "https://www.youtube.com/watch?v=0123456789a&other=ignored&more=alsoignored",
+                "https://www.youtube.com/watch?v=0123456789a",
+            ),
+        ];
+
+        for (url, exp_ret) in &should_succeed {
+            assert!(
+                validate_youtube_url(&reqwest::Client::new(), 1, UrlKind::Video, url)
+                    .await
+                    .is_ok_and(|v| v.url == *exp_ret)
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_validate_url_dispatch() {
+        let http_client = reqwest::Client::new();
+
+        // URLs of domains no platform is registered for are rejected before any
+        // platform-specific validation takes place.
+        let should_error = [
+            ("", "Empty video URL"),
+            (
+                "https://vimeo.com/123456789",
+                "Unsupported or invalid video URL",
+            ),
+            (
+                "https://youtube.org/watch?v=0123456789a",
+                "Unsupported or invalid video URL",
+            ),
+            (
+                "https://notyoutube.com/watch?v=0123456789a",
+                "Unsupported or invalid video URL",
+            ),
+        ];
+
+        for (url, exp_err) in &should_error {
+            assert!(
+                validate_url(&http_client, 1, UrlKind::Video, url)
+                    .await
+                    .is_err_and(|e| e.to_string() == *exp_err),
+                "{url}"
+            );
+        }
+
+        assert!(platform_for_url("https://vimeo.com/123456789").is_none());
+        assert!(platform_for_url("https://www.youtube.com/watch?v=0123456789a").is_some());
+
+        // URLs of registered domains are validated by the platform's validator.
+        assert!(
+            validate_url(
+                &http_client,
+                1,
+                UrlKind::Video,
+                "https://www.youtube.com/watch?v=0123456789a&t=42"
+            )
+            .await
+            .is_ok_and(
+                |v| v.url == "https://www.youtube.com/watch?v=0123456789a" && v.rss_url.is_none()
+            )
+        );
+    }
+
+    #[test]
+    fn test_canonical_youtube_channel_url() {
+        let should_succeed = [
+            ("youtube.com/@Test", "https://www.youtube.com/@test"),
+            (
+                "https://www.youtube.com/@Test/videos",
+                "https://www.youtube.com/@test",
+            ),
+            (
+                "http://youtube.com/channel/UC0123456789abcdefghi-_A",
+                "https://www.youtube.com/channel/UC0123456789abcdefghi-_A",
+            ),
+            (
+                "https://www.youtube.com/channel/UC0123456789abcdefghi-_A/videos",
+                "https://www.youtube.com/channel/UC0123456789abcdefghi-_A",
+            ),
+        ];
+
+        for (url, exp_ret) in &should_succeed {
+            assert_eq!(
+                canonical_youtube_channel_url(url).as_deref(),
+                Some(*exp_ret),
+                "{url}"
+            );
+        }
+
+        let should_error = [
+            "https://www.youtube.com/watch?v=0123456789a",
+            "https://www.youtube.com/channel/UC0123456789abcdefghi-_",
+            "https://www.youtube.com/channel/XY0123456789abcdefghi-_A",
+            "https://www.youtube.com/channel/UC0123456789abcdef<>i-_A",
+        ];
+
+        for url in &should_error {
+            assert!(canonical_youtube_channel_url(url).is_none(), "{url}");
+        }
+
+        assert_eq!(
+            channel_id_from_rss_url(
+                "https://www.youtube.com/feeds/videos.xml?channel_id=UC0123456789abcdefghi-_A"
+            ),
+            Some("UC0123456789abcdefghi-_A")
+        );
+    }
+
+    #[test]
+    fn test_fallback_channel_id() {
+        let exp_channel_id = "UC0123456789abcdefghi-_A";
+
+        // Channel URLs of the '/channel/UC...' form carry the ID themselves.
+        assert_eq!(
+            fallback_channel_id(
+                "https://www.youtube.com/channel/UC0123456789abcdefghi-_A",
+                ""
+            )
+            .as_deref(),
+            Some(exp_channel_id)
+        );
+
+        let should_succeed = [
+            r#"<meta property="og:url" content="https://www.youtube.com/channel/UC0123456789abcdefghi-_A">"#,
+            r#"<link rel="canonical" href="https://www.youtube.com/channel/UC0123456789abcdefghi-_A">"#,
+            r#"{"metadata":{"externalId":"UC0123456789abcdefghi-_A"}}"#,
+            r#"{"channelId": "UC0123456789abcdefghi-_A"}"#,
+        ];
+
+        for webpage in &should_succeed {
+            assert_eq!(
+                fallback_channel_id("https://www.youtube.com/@test", webpage).as_deref(),
+                Some(exp_channel_id),
+                "{webpage}"
+            );
+        }
+
+        let should_error = [
+            "",
+            r#"{"externalId":"UC0123456789abcdefghi-_"}"#,
+            r#"{"externalId":"UC0123456789abcdefghi-_AB"}"#,
+            r#"{"videoId":"UC0123456789abcdefghi-_A"}"#,
+        ];
+
+        for webpage in &should_error {
+            assert!(
+                fallback_channel_id("https://www.youtube.com/@test", webpage).is_none(),
+                "{webpage}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_extract_channel_image_urls() {
+        // Avatar from the 'og:image' meta element takes precedence over the one in
+        // the channel JSON, and escaped characters are unescaped.
+        let webpage = r#"<meta content="https://yt3.googleusercontent.com/og=s900?a=1&amp;b=2" property="og:image">
+            {"avatar":{"thumbnails":[{"url":"https://yt3.googleusercontent.com/json=s48"}]},
+            "banner":{"imageBannerViewModel":{"image":{"sources":[{"url":"https:\/\/yt3.googleusercontent.com\/banner=w1060?x=1\u0026y=2","width":1060}]}}}}"#;
+        assert_eq!(
+            extract_channel_image_urls(webpage),
+            (
+                Some("https://yt3.googleusercontent.com/og=s900?a=1&b=2".to_string()),
+                Some("https://yt3.googleusercontent.com/banner=w1060?x=1&y=2".to_string()),
+            )
+        );
+
+        // Without the meta element, the avatar is taken from the channel JSON.
+        let webpage =
+            r#"{"avatar":{"thumbnails":[{"url":"https://yt3.googleusercontent.com/json=s48"}]}}"#;
+        assert_eq!(
+            extract_channel_image_urls(webpage),
+            (
+                Some("https://yt3.googleusercontent.com/json=s48".to_string()),
+                None
+            )
+        );
+
+        // Non-HTTPS URLs and pages without images yield nothing.
+        let webpage = r#"<meta property="og:image" content="javascript:alert(1)">"#;
+        assert_eq!(extract_channel_image_urls(webpage), (None, None));
+        assert_eq!(extract_channel_image_urls("<html></html>"), (None, None));
+    }
+
+    #[test]
+    fn test_extract_channel_rss_url() {
+        let exp_rss_url =
+            "https://www.youtube.com/feeds/videos.xml?channel_id=UC0123456789abcdefghi-_A";
+
+        // Below webpage snippets vary the markup surrounding the RSS feed link
+        // element, but should all result in the same RSS feed URL being extracted.
+        let should_succeed = [
+            r#"<html><link rel="alternate" type="application/rss+xml" title="RSS" href="https://www.youtube.com/feeds/videos.xml?channel_id=UC0123456789abcdefghi-_A"></html>"#,
+            r#"<link rel="alternate" type="application/rss+xml" title="RSS" href="https://www.youtube.com/feeds/videos.xml?channel_id=UC0123456789abcdefghi-_A"/>"#,
+            r#"<link type="application/rss+xml" rel="alternate" href="https://www.youtube.com/feeds/videos.xml?channel_id=UC0123456789abcdefghi-_A" title="RSS">"#,
+            r"<link  rel='alternate'  type='application/rss+xml'  title='Channel RSS'  href='https://www.youtube.com/feeds/videos.xml?channel_id=UC0123456789abcdefghi-_A'>",
+            "<link rel=\"alternate\"\n    type=\"application/rss+xml\"\n    href=\"https://www.youtube.com/feeds/videos.xml?channel_id=UC0123456789abcdefghi-_A\">",
+            r#"<link rel="canonical" href="https://www.youtube.com/channel/UCzzzzzzzzzzzzzzzzzzzzzz"><link rel="alternate" type="application/rss+xml" title="RSS" href="https://www.youtube.com/feeds/videos.xml?channel_id=UC0123456789abcdefghi-_A">"#,
+        ];
+
+        for webpage in &should_succeed {
+            assert!(extract_channel_rss_url(webpage).is_ok_and(|u| {
+                if u == exp_rss_url {
+                    true
+                } else {
+                    println!("{webpage}: Expected '{exp_rss_url}', but got '{u}'");
+                    false
+                }
+            }));
+        }
+
+        // Below webpage snippets either lack the RSS feed link element or contain a
+        // malformed channel ID, and should thus all produce an Error result.
+        let should_error = [
+            "",
+            "<html></html>",
+            r#"<link rel="canonical" href="https://www.youtube.com/channel/UC0123456789abcdefghi-_A">"#,
+            r#"<link rel="alternate" type="application/rss+xml" title="RSS" href="https://www.youtube.com/feeds/videos.xml?channel_id=UC0123456789abcdefghi-_">"#,
+            r#"<link rel="alternate" type="application/rss+xml" title="RSS" href="https://www.youtube.com/feeds/videos.xml?channel_id=UC0123456789abcdefghi-_AB">"#,
+            r#"<link rel="alternate" type="application/rss+xml" title="RSS" href="https://www.youtube.com/feeds/videos.xml?channel_id=XY0123456789abcdefghi-_A">"#,
+            r#"<link rel="alternate" type="application/rss+xml" title="RSS" href="https://www.youtube.com/feeds/videos.xml?channel_id=UC0123456789abcdef<>i-_A">"#,
+        ];
+
+        for webpage in &should_error {
+            assert!(extract_channel_rss_url(webpage).is_err(), "{webpage}");
+        }
+    }
+}
@@ -1,12 +1,261 @@
+use crate::jobs::block_on_task;
+use tracing::{Level, event};
+
+// Pattern extracting the URL and publication date of each video from the
+// entries of a YouTube channel's RSS feed.
+pub(crate) const VIDEOS_RE: &str = r#"<entry>(?s:.+?)<link rel="alternate" href="(https://www\.youtube\.com/watch\?v=.{11})"/>(?s:.+?)<published>(\d{4}-\d{2}-\d{2}T\d{2}:\d{2}:\d{2}\+\d{2}:\d{2})</published>(?s:.+?)</entry>"#;
+
+// Pattern extracting title, URL, publication date, and description of each
+// video from the entries of a YouTube channel's RSS feed.
+pub(crate) const RECORDS_RE: &str = r#"<entry>(?s:.+?)<title>(?s:(.*?))</title>(?s:.+?)<link rel="alternate" href="(https://www\.youtube\.com/watch\?v=.{11})"/>(?s:.+?)<published>(\d{4}-\d{2}-\d{2}T\d{2}:\d{2}:\d{2}\+\d{2}:\d{2})</published>(?s:.+?)<media:description>(?s:(.*?))</media:description>(?s:.*?)</entry>"#;
+
+// Maximum number of videos a YouTube channel's RSS feed lists, no matter how
+// many videos the channel has published.
+pub(crate) const FEED_MAX_VIDEOS: usize = 15;
+
+// Return the URL of the playlist containing all uploads of the YouTube channel
+// with the supplied RSS feed URL. The ID of this playlist is the channel ID
+// with its 'UC' prefix replaced by 'UU'.
+pub(crate) fn channel_uploads_playlist_url(rss_url: &str) -> Option<String> {
+    let (_, channel_id) = rss_url.split_once("channel_id=")?;
+    let uploads_id = channel_id.strip_prefix("UC")?;
+    Some(format!(
+        "https://www.youtube.com/playlist?list=UU{uploads_id}"
+    ))
+}
+
+#[derive(Clone, Debug)]
+/// The metadata of a single video as found in a `YouTube` channel's RSS feed.
+pub(crate) struct VideoRecord {
+    pub(crate) url: String,
+    pub(crate) title: String,
+    pub(crate) published: chrono::DateTime<chrono::FixedOffset>,
+    pub(crate) description: String,
+}
+
+// Number of seconds to wait before the first retry of a failed RSS feed fetch.
+// The wait doubles with each further retry.
+const FETCH_BACKOFF_SECS: u64 = 2;
+
+#[derive(Debug)]
+/// Error of an RSS feed fetch that `YouTube` rejected with status 429 (Too Many
+/// Requests), carrying the point in time from which on `YouTube` permits fetches
+/// again if it announced one via the `Retry-After` header.
+pub(crate) struct RateLimited {
+    pub(crate) retry_after: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl std::fmt::Display for RateLimited {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.retry_after {
+            Some(at) => write!(f, "Rate-limited by YouTube until {at}"),
+            None => write!(f, "Rate-limited by YouTube"),
+        }
+    }
+}
+
+impl std::error::Error for RateLimited {}
+
+// Maximum number of hours to honor a `Retry-After` header for, so that a single
+// bogus header can't postpone a channel's checks indefinitely.
+const RETRY_AFTER_MAX_HOURS: i64 = 24;
+
+// Parse the value of a `Retry-After` header, which is either a number of seconds
+// to wait or an HTTP date, into the point in time it denotes relative to `now`,
+// capped at `RETRY_AFTER_MAX_HOURS` from `now`.
+fn parse_retry_after(
+    value: &str,
+    now: chrono::DateTime<chrono::Utc>,
+) -> Option<chrono::DateTime<chrono::Utc>> {
+    let value = value.trim();
+    let at = match value.parse::<u32>() {
+        Ok(secs) => now.checked_add_signed(chrono::TimeDelta::seconds(i64::from(secs)))?,
+        Err(_) => chrono::DateTime::parse_from_rfc2822(value)
+            .ok()?
+            .with_timezone(&chrono::Utc),
+    };
+
+    let latest = now.checked_add_signed(chrono::TimeDelta::hours(RETRY_AFTER_MAX_HOURS))?;
+    if at > latest {
+        event!(
+            Level::WARN,
+            "YouTube asked to retry fetching RSS feeds only at {at} (Retry-After: {value}), capping it at {latest}",
+        );
+        return Some(latest);
+    }
+    Some(at)
+}
+
+#[derive(Clone, Debug)]
+/// Fetches the RSS feeds of `YouTube` channels, identifying itself to `YouTube`
+/// via the configured user agent and, if configured, proxy and additional
+/// request headers. Failed fetches are retried with exponential
+/// backoff up to the configured number of attempts. At most `max_per_host`
+/// fetches are in flight towards the same host at any time, shared among all
+/// clones of a fetcher. Also counts the fetched feeds containing entries that
+/// failed to parse.
+pub(crate) struct FeedFetcher {
+    user_agent: String,
+    proxy: Option<reqwest::Proxy>,
+    headers: reqwest::header::HeaderMap,
+    attempts: u8,
+    max_per_host: usize,
+    host_permits: std::sync::Arc<
+        std::sync::Mutex<std::collections::HashMap<String, std::sync::Arc<tokio::sync::Semaphore>>>,
+    >,
+    unparsed_feeds: std::sync::Arc<std::sync::atomic::AtomicU64>,
+}
+
+impl FeedFetcher {
+    pub(crate) fn new(
+        user_agent: String,
+        proxy: Option<reqwest::Proxy>,
+        headers: reqwest::header::HeaderMap,
+        attempts: u8,
+        max_per_host: usize,
+    ) -> Self {
+        Self {
+            user_agent,
+            proxy,
+            headers,
+            attempts,
+            max_per_host,
+            host_permits: std::sync::Arc::default(),
+            unparsed_feeds: std::sync::Arc::default(),
+        }
+    }
+
+    /// Return a handle to the number of fetched feeds so far in which fewer entries
+    /// were parsed than present, shared among all clones of this fetcher.
+    pub(crate) fn unparsed_feeds(&self) -> std::sync::Arc<std::sync::atomic::AtomicU64> {
+        std::sync::Arc::clone(&self.unparsed_feeds)
+    }
+
+    // Compare the number of entries present in the supplied RSS feed data with the
+    // number of them that were parsed. If they differ, the parsing patterns likely
+    // no longer match the feed format YouTube serves, which would make autotube
+    // silently miss videos, so log it prominently and count the feed.
+    fn report_parse_coverage(&self, rss_url: &str, rss_data: &str, num_parsed: usize) {
+        let num_entries = count_entries(rss_data);
+        if num_parsed != num_entries {
+            self.unparsed_feeds
+                .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+            event!(
+                Level::WARN,
+                "Parsed only {num_parsed} of {num_entries} entries of RSS feed {rss_url}, its format may have changed",
+            );
+        }
+    }
+
+    // Return the semaphore limiting the number of concurrent fetches towards the
+    // host of the supplied URL, creating it upon the first fetch from that host.
+    fn host_semaphore(
+        &self,
+        rss_url: &str,
+    ) -> anyhow::Result<std::sync::Arc<tokio::sync::Semaphore>> {
+        let parsed_url = reqwest::Url::parse(rss_url)?;
+        let Some(host) = parsed_url.host_str() else {
+            return Err(anyhow::anyhow!("RSS feed URL {rss_url} lacks a host"));
+        };
+
+        let Ok(mut host_permits) = self.host_permits.lock() else {
+            return Err(anyhow::anyhow!("Lock on RSS feed host permits is poisoned"));
+        };
+
+        Ok(std::sync::Arc::clone(
+            host_permits.entry(host.to_lowercase()).or_insert_with(|| {
+                std::sync::Arc::new(tokio::sync::Semaphore::new(self.max_per_host))
+            }),
+        ))
+    }
+
+    // Obtain the the YouTube channel's RSS feed using a blocking reqwest client and
+    // extract the body as text. The client is constructed per request, as blocking
+    // clients must not be created or dropped within the async runtime. Waits for a
+    // free slot among the concurrent fetches permitted towards the feed's host
+    // first, blocking the calling thread while a task on the current runtime
+    // acquires it.
+    fn fetch_once(&self, rss_url: &str) -> anyhow::Result<String> {
+        let semaphore = self.host_semaphore(rss_url)?;
+        let _permit = block_on_task(
+            &tokio::runtime::Handle::current(),
+            semaphore.acquire_owned(),
+        )??;
+
+        let mut builder = reqwest::blocking::Client::builder()
+            .user_agent(&self.user_agent)
+            .default_headers(self.headers.clone());
+        if let Some(proxy) = &self.proxy {
+            builder = builder.proxy(proxy.clone());
+        }
+        let client = builder.build()?;
+        let resp = client.get(rss_url).send()?;
+        if resp.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            let retry_after = resp
+                .headers()
+                .get(reqwest::header::RETRY_AFTER)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| parse_retry_after(v, chrono::Utc::now()));
+            return Err(RateLimited { retry_after }.into());
+        }
+        // Error pages contain no entries and would pass as a feed without new videos,
+        // making the check skip the videos published in the meantime for good.
+        Ok(resp.error_for_status()?.text()?)
+    }
+
+    // Obtain the YouTube channel's RSS feed, retrying failed attempts, including
+    // those answered with an error status, after waiting for an exponentially
+    // growing backoff, so that a brief network outage doesn't make a channel check
+    // or follow fail entirely. Blocks the calling thread while waiting. Being
+    // rate-limited is not retried, but returned as `RateLimited` error right away,
+    // so that callers can back off for as long as requested.
+    pub(crate) fn fetch(&self, rss_url: &str) -> anyhow::Result<String> {
+        let mut attempt: u8 = 1;
+        let mut backoff_secs = FETCH_BACKOFF_SECS;
+        loop {
+            match self.fetch_once(rss_url) {
+                Ok(rss_data) => return Ok(rss_data),
+                Err(e) if attempt < self.attempts && !e.is::<RateLimited>() => {
+                    event!(
+                        Level::INFO,
+                        "Attempt {attempt} of {} to fetch RSS feed {rss_url} failed, retrying in {backoff_secs}s: {e}",
+                        self.attempts,
+                    );
+                    std::thread::sleep(std::time::Duration::from_secs(backoff_secs));
+                    attempt = attempt.saturating_add(1);
+                    backoff_secs = backoff_secs.saturating_mul(2);
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+// Return the number of entries, i.e., videos, present in the supplied RSS feed
+// data, regardless of whether they can be parsed.
+fn count_entries(rss_data: &str) -> usize {
+    rss_data.matches("<entry>").count()
+}
+
+// Replace the predefined XML entities in text extracted from an RSS feed with
+// the characters they stand for.
+fn unescape_xml(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
 // Return the list of videos found in the YouTube channel's RSS feed as tuples
 // <publication timestamp, video URL>, sorted from most recent to least recent.
 fn channel_get_most_recent_videos(
+    fetcher: &FeedFetcher,
     videos_re: &regex::Regex,
     rss_url: &str,
 ) -> anyhow::Result<Vec<(chrono::DateTime<chrono::FixedOffset>, String)>> {
-    // Obtain the the YouTube channel's RSS feed using reqwest's blocking GET
-    // function and extract the body as text.
-    let rss_data = reqwest::blocking::get(rss_url)?.text()?;
+    let rss_data = fetcher.fetch(rss_url)?;
 
     // Extract the <publication date, video URL> tuple for all videos found
     // wrapped inside <entry></entry> in the YouTube channel's RSS feed.
@@ -20,6 +269,7 @@ fn channel_get_most_recent_videos(
 
         videos.push((parsed_pub_date, video_url.to_string()));
     }
+    fetcher.report_parse_coverage(rss_url, &rss_data, videos.len());
 
     // Sort tuple vector by publication date entries, newest to oldest.
     videos.sort_by(|(t1, _), (t2, _)| t2.cmp(t1));
@@ -28,35 +278,31 @@ fn channel_get_most_recent_videos(
 }
 
 // From the sorted list of videos of a YouTube channel, return the URLs to the
-// `num_items` most recent ones.
+// `num_items` most recent ones along with their publication timestamps.
 pub(crate) fn channel_get_n_most_recent_videos(
+    fetcher: &FeedFetcher,
     videos_re: &regex::Regex,
     rss_url: &str,
     num_items: u8,
-) -> anyhow::Result<Vec<String>> {
-    // Obtain sorted list of <publication timestamp, video URL> tuples of channel.
-    let most_recent_videos = channel_get_most_recent_videos(videos_re, rss_url)?;
-
-    // Select only the specified number of items from the front of sorted videos
-    // list and discard the publication times, leaving only their URLs.
-    let (_, n_most_recent_videos): (Vec<chrono::DateTime<chrono::FixedOffset>>, Vec<String>) =
-        most_recent_videos
-            .into_iter()
-            .take(num_items.into())
-            .unzip();
+) -> anyhow::Result<Vec<(chrono::DateTime<chrono::FixedOffset>, String)>> {
+    // Obtain sorted list of <publication timestamp, video URL> tuples of channel
+    // and select only the specified number of items from its front.
+    let mut most_recent_videos = channel_get_most_recent_videos(fetcher, videos_re, rss_url)?;
+    most_recent_videos.truncate(num_items.into());
 
-    Ok(n_most_recent_videos)
+    Ok(most_recent_videos)
 }
 
 // From the sorted list of videos of a YouTube channel, return the URLs to the
 // ones that were published at or after the `as_of` timestamp.
 pub(crate) fn channel_get_videos_as_of(
+    fetcher: &FeedFetcher,
     videos_re: &regex::Regex,
     rss_url: &str,
     as_of: chrono::DateTime<chrono::FixedOffset>,
 ) -> anyhow::Result<Vec<String>> {
     // Obtain sorted list of <publication timestamp, video URL> tuples of channel.
-    let most_recent_videos = channel_get_most_recent_videos(videos_re, rss_url)?;
+    let most_recent_videos = channel_get_most_recent_videos(fetcher, videos_re, rss_url)?;
 
     // Select only the videos from the sorted list that were published at or after
     // the supplied `as_of` timestamp and discard the publication times, leaving
@@ -69,3 +315,298 @@ pub(crate) fn channel_get_videos_as_of(
 
     Ok(videos_as_of)
 }
+
+// Return the metadata records of all videos found in the YouTube channel's RSS
+// feed, sorted from most recent to least recent.
+fn channel_get_most_recent_records(
+    fetcher: &FeedFetcher,
+    records_re: &regex::Regex,
+    rss_url: &str,
+) -> anyhow::Result<Vec<VideoRecord>> {
+    let rss_data = fetcher.fetch(rss_url)?;
+    let records = parse_records(records_re, &rss_data)?;
+    fetcher.report_parse_coverage(rss_url, &rss_data, records.len());
+    Ok(records)
+}
+
+// Parse the metadata records of all videos out of the supplied RSS feed data,
+// sorted from most recent to least recent.
+fn parse_records(records_re: &regex::Regex, rss_data: &str) -> anyhow::Result<Vec<VideoRecord>> {
+    // Extract title, video URL, publication date, and description for all videos
+    // found wrapped inside <entry></entry> in the YouTube channel's RSS feed.
+    let mut records: Vec<VideoRecord> = vec![];
+    for (_, [title, video_url, pub_date, description]) in
+        records_re.captures_iter(rss_data).map(|c| c.extract())
+    {
+        let Ok(published) = pub_date.parse::<chrono::DateTime<chrono::FixedOffset>>() else {
+            return Err(anyhow::anyhow!(format!(
+                "Couldn't parse publication date {pub_date} into valid chrono date"
+            )));
+        };
+
+        records.push(VideoRecord {
+            url: video_url.to_string(),
+            title: unescape_xml(title),
+            published,
+            description: unescape_xml(description),
+        });
+    }
+
+    // Sort records by publication date, newest to oldest.
+    records.sort_by_key(|r| std::cmp::Reverse(r.published));
+
+    Ok(records)
+}
+
+// From the sorted list of video records of a YouTube channel, return the
+// `num_items` most recent ones.
+pub(crate) fn channel_get_n_most_recent_records(
+    fetcher: &FeedFetcher,
+    records_re: &regex::Regex,
+    rss_url: &str,
+    num_items: u8,
+) -> anyhow::Result<Vec<VideoRecord>> {
+    let mut records = channel_get_most_recent_records(fetcher, records_re, rss_url)?;
+    records.truncate(num_items.into());
+    Ok(records)
+}
+
+// From the sorted list of video records of a YouTube channel, return the ones
+// of videos that were published at or after the `as_of` timestamp.
+pub(crate) fn channel_get_records_as_of(
+    fetcher: &FeedFetcher,
+    records_re: &regex::Regex,
+    rss_url: &str,
+    as_of: chrono::DateTime<chrono::FixedOffset>,
+) -> anyhow::Result<Vec<VideoRecord>> {
+    let mut records = channel_get_most_recent_records(fetcher, records_re, rss_url)?;
+    records.retain(|r| r.published >= as_of);
+    Ok(records)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    // Answer all HTTP requests to a local port with the supplied status and an HTML
+    // error page in a thread of its own. Returns the URL of a channel's RSS feed
+    // served there along with the number of requests answered so far.
+    pub(crate) fn serve_status(
+        status: u16,
+    ) -> (String, std::sync::Arc<std::sync::atomic::AtomicUsize>) {
+        use std::io::{BufRead, Write};
+
+        let Ok(listener) = std::net::TcpListener::bind("127.0.0.1:0") else {
+            panic!("Failed to bind local test server");
+        };
+        let Ok(addr) = listener.local_addr() else {
+            panic!("Failed to obtain address of local test server");
+        };
+        let requests = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+
+        let served = std::sync::Arc::clone(&requests);
+        std::thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                let mut reader = std::io::BufReader::new(&stream);
+                let mut line = String::new();
+                while reader.read_line(&mut line).is_ok_and(|n| n > 2) {
+                    line.clear();
+                }
+                served.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                let body = "<html><body>Error</body></html>";
+                let _ = write!(
+                    stream,
+                    "HTTP/1.1 {status} Error\r\nContent-Type: text/html\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+            }
+        });
+
+        (
+            format!("http://{addr}/feeds/videos.xml?channel_id=UC0123456789abcdefghijkl"),
+            requests,
+        )
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_fetch_error_status() {
+        let Ok(videos_re) = regex::Regex::new(VIDEOS_RE) else {
+            panic!("Failed to compile videos pattern");
+        };
+
+        // Error pages are failed fetches, which are retried, rather than feeds
+        // without any videos.
+        let fetches = [404, 500, 503].map(|status| {
+            let (rss_url, requests) = serve_status(status);
+            let fetcher =
+                FeedFetcher::new(String::new(), None, reqwest::header::HeaderMap::new(), 2, 1);
+            let videos_re = videos_re.clone();
+            let fetch = tokio::task::spawn_blocking(move || {
+                channel_get_n_most_recent_videos(&fetcher, &videos_re, &rss_url, 15)
+            });
+            (fetch, requests)
+        });
+        for (fetch, requests) in fetches {
+            assert!(fetch.await.is_ok_and(|r| r.is_err()));
+            assert_eq!(requests.load(std::sync::atomic::Ordering::SeqCst), 2);
+        }
+    }
+
+    #[test]
+    fn test_host_semaphore() {
+        let fetcher =
+            FeedFetcher::new(String::new(), None, reqwest::header::HeaderMap::new(), 1, 2);
+
+        let (Ok(s1), Ok(s2), Ok(s3)) = (
+            fetcher.host_semaphore("https://www.youtube.com/feeds/videos.xml?channel_id=UC1"),
+            fetcher
+                .clone()
+                .host_semaphore("https://WWW.youtube.com/feeds/videos.xml?channel_id=UC2"),
+            fetcher.host_semaphore("https://example.org/feed.xml"),
+        ) else {
+            panic!("Failed to obtain host semaphores");
+        };
+
+        // Fetches towards the same host share their permits, also across clones.
+        assert!(std::sync::Arc::ptr_eq(&s1, &s2));
+        assert!(!std::sync::Arc::ptr_eq(&s1, &s3));
+        assert_eq!(s1.available_permits(), 2);
+
+        assert!(fetcher.host_semaphore("not a URL").is_err());
+    }
+
+    #[test]
+    fn test_parse_retry_after() {
+        let now = chrono::DateTime::from_timestamp(1_700_000_000, 0).unwrap_or_default();
+
+        assert_eq!(
+            parse_retry_after("120", now),
+            Some(now + chrono::TimeDelta::seconds(120))
+        );
+        assert_eq!(parse_retry_after(" 0 ", now), Some(now));
+        assert_eq!(
+            parse_retry_after("Wed, 15 Nov 2023 00:00:00 GMT", now),
+            chrono::DateTime::from_timestamp(1_700_006_400, 0)
+        );
+        assert!(parse_retry_after("-5", now).is_none());
+
+        // Announcements too far in the future are capped.
+        let latest = now + chrono::TimeDelta::hours(RETRY_AFTER_MAX_HOURS);
+        assert_eq!(parse_retry_after("4294967295", now), Some(latest));
+        assert_eq!(
+            parse_retry_after("Fri, 01 Jan 2100 00:00:00 GMT", now),
+            Some(latest)
+        );
+        assert!(parse_retry_after("soon", now).is_none());
+    }
+
+    #[test]
+    fn test_channel_uploads_playlist_url() {
+        assert_eq!(
+            channel_uploads_playlist_url(
+                "https://www.youtube.com/feeds/videos.xml?channel_id=UC0123456789abcdefghi-_A"
+            )
+            .as_deref(),
+            Some("https://www.youtube.com/playlist?list=UU0123456789abcdefghi-_A")
+        );
+        assert!(channel_uploads_playlist_url("https://www.youtube.com/feeds/videos.xml").is_none());
+    }
+
+    #[test]
+    fn test_unescape_xml() {
+        assert_eq!(unescape_xml("plain"), "plain");
+        assert_eq!(
+            unescape_xml("Tom &amp; Jerry &lt;3 &quot;cheese&quot; &#39;n&apos; more"),
+            "Tom & Jerry <3 \"cheese\" 'n' more"
+        );
+        assert_eq!(unescape_xml("&amp;lt;"), "&lt;");
+    }
+
+    #[test]
+    fn test_parse_records() {
+        let rss_data = r#"<?xml version="1.0" encoding="UTF-8"?>
+<feed xmlns:yt="http://www.youtube.com/xml/schemas/2015" xmlns:media="http://search.yahoo.com/mrss/" xmlns="http://www.w3.org/2005/Atom">
+ <link rel="self" href="http://www.youtube.com/feeds/videos.xml?channel_id=UC0123456789abcdefghijkl"/>
+ <title>Some Channel</title>
+ <entry>
+  <id>yt:video:AAAAAAAAAAA</id>
+  <yt:videoId>AAAAAAAAAAA</yt:videoId>
+  <title>Older &amp; wiser</title>
+  <link rel="alternate" href="https://www.youtube.com/watch?v=AAAAAAAAAAA"/>
+  <published>2024-01-01T10:00:00+00:00</published>
+  <updated>2024-01-02T10:00:00+00:00</updated>
+  <media:group>
+   <media:title>Older &amp; wiser</media:title>
+   <media:description>First line
+second line</media:description>
+  </media:group>
+ </entry>
+ <entry>
+  <id>yt:video:BBBBBBBBBBB</id>
+  <yt:videoId>BBBBBBBBBBB</yt:videoId>
+  <title>Newest</title>
+  <link rel="alternate" href="https://www.youtube.com/watch?v=BBBBBBBBBBB"/>
+  <published>2024-02-01T10:00:00+00:00</published>
+  <updated>2024-02-01T10:00:00+00:00</updated>
+  <media:group>
+   <media:title>Newest</media:title>
+   <media:description></media:description>
+  </media:group>
+ </entry>
+</feed>"#;
+
+        let Ok(records_re) = regex::Regex::new(RECORDS_RE) else {
+            panic!("Failed to compile RECORDS_RE");
+        };
+        let Ok(records) = parse_records(&records_re, rss_data) else {
+            panic!("Failed to parse records from RSS feed");
+        };
+
+        assert_eq!(records.len(), 2);
+        assert_eq!(
+            records[0].url,
+            "https://www.youtube.com/watch?v=BBBBBBBBBBB"
+        );
+        assert_eq!(records[0].title, "Newest");
+        assert_eq!(records[0].description, "");
+        assert_eq!(
+            records[1].url,
+            "https://www.youtube.com/watch?v=AAAAAAAAAAA"
+        );
+        assert_eq!(records[1].title, "Older & wiser");
+        assert_eq!(records[1].description, "First line\nsecond line");
+    }
+
+    #[test]
+    fn test_report_parse_coverage() {
+        let rss_data = r#"<feed>
+ <entry>
+  <link rel="alternate" href="https://www.youtube.com/watch?v=AAAAAAAAAAA"/>
+  <published>2024-01-01T10:00:00+00:00</published>
+ </entry>
+ <entry>
+  <link rel="alternate" href="https://www.youtube.com/watch?v=BBBBBBBBBBB"/>
+  <published>2024-02-01</published>
+ </entry>
+</feed>"#;
+        assert_eq!(count_entries(rss_data), 2);
+        assert_eq!(count_entries("<feed></feed>"), 0);
+
+        let Ok(videos_re) = regex::Regex::new(VIDEOS_RE) else {
+            panic!("Failed to compile VIDEOS_RE");
+        };
+        let num_parsed = videos_re.captures_iter(rss_data).count();
+        assert_eq!(num_parsed, 1);
+
+        // Only feeds with unparsed entries are counted, also across clones.
+        let fetcher =
+            FeedFetcher::new(String::new(), None, reqwest::header::HeaderMap::new(), 1, 2);
+        let unparsed_feeds = fetcher.unparsed_feeds();
+        fetcher.report_parse_coverage("https://example.org/feed.xml", rss_data, 2);
+        assert_eq!(unparsed_feeds.load(std::sync::atomic::Ordering::Relaxed), 0);
+        fetcher
+            .clone()
+            .report_parse_coverage("https://example.org/feed.xml", rss_data, num_parsed);
+        assert_eq!(unparsed_feeds.load(std::sync::atomic::Ordering::Relaxed), 1);
+    }
+}
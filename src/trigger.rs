@@ -1,9 +1,17 @@
-use crate::jobs::{Job, JobCheckChannel};
+use crate::db;
+use crate::jobs::{Job, JobCheckChannel, JobQueue};
+use crate::worker::ActiveDownloads;
 use rand::distr::Distribution;
 use rand::prelude::SliceRandom;
+use std::sync::atomic::{AtomicU64, Ordering};
 use tracing::{Level, event};
 
-enum Frequencies {
+// Number of seconds a throttled trigger waits before looking at the number of
+// downloads in progress again.
+const THROTTLE_POLL_SECS: u64 = 30;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Frequencies {
     Often,
     Sometimes,
     Rarely,
@@ -16,6 +24,15 @@ impl Frequencies {
         Frequencies::Rarely,
     ];
 
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "often" => Some(Frequencies::Often),
+            "sometimes" => Some(Frequencies::Sometimes),
+            "rarely" => Some(Frequencies::Rarely),
+            _ => None,
+        }
+    }
+
     fn get_dur_mins(&self) -> u64 {
         match self {
             Frequencies::Often => 120,     // every  2 hours
@@ -35,6 +52,85 @@ impl std::fmt::Display for Frequencies {
     }
 }
 
+// Estimate when a channel followed with the supplied frequency, which was last
+// checked at `last_checked` (in RFC 3339 format), will be checked next: one full
+// interval of its frequency after its last check. As the checks of each interval
+// are spread across it and shifted by random jitter, this is only approximate.
+pub(crate) fn estimate_next_check(
+    frequency: &str,
+    last_checked: &str,
+) -> Option<chrono::DateTime<chrono::FixedOffset>> {
+    let dur_mins = Frequencies::from_name(frequency)?.get_dur_mins();
+    chrono::DateTime::parse_from_rfc3339(last_checked)
+        .ok()?
+        .checked_add_signed(chrono::TimeDelta::minutes(i64::try_from(dur_mins).ok()?))
+}
+
+// Verifies that the supplied fraction of a check interval across which the
+// checks of all channels are spread lies within (0.0, 1.0]. A fraction of 1.0
+// spreads the checks across the full interval.
+pub(crate) fn parse_check_spread(spread: &str) -> anyhow::Result<f64> {
+    match spread.trim().parse::<f64>() {
+        Ok(s) if s > 0.0 && s <= 1.0 => Ok(s),
+        _ => Err(anyhow::anyhow!(
+            "Check spread needs to be a number greater than 0.0 and at most 1.0"
+        )),
+    }
+}
+
+// Verifies that the supplied jitter, as a proportion of the average time between
+// two checks, lies within [0.0, 1.0]. As the jitter is sampled from both sides of
+// zero, larger proportions could result in negative sleeps.
+pub(crate) fn parse_check_jitter(jitter: &str) -> anyhow::Result<f64> {
+    match jitter.trim().parse::<f64>() {
+        Ok(j) if (0.0..=1.0).contains(&j) => Ok(j),
+        _ => Err(anyhow::anyhow!(
+            "Check jitter needs to be a number between 0.0 and 1.0"
+        )),
+    }
+}
+
+// Parses an override of a check parameter for the channels of one frequency of
+// the form '<FREQUENCY>=<VALUE>', validating the value via the supplied parser.
+fn parse_frequency_override(
+    arg: &str,
+    parse_value: fn(&str) -> anyhow::Result<f64>,
+) -> anyhow::Result<(Frequencies, f64)> {
+    let Some((frequency, value)) = arg.split_once('=') else {
+        return Err(anyhow::anyhow!(
+            "Per-frequency value needs to be of the form '<FREQUENCY>=<VALUE>'"
+        ));
+    };
+    let Some(frequency) = Frequencies::from_name(frequency.trim()) else {
+        return Err(anyhow::anyhow!(
+            "Frequency needs to be one of: 'often', 'sometimes', 'rarely'"
+        ));
+    };
+    Ok((frequency, parse_value(value)?))
+}
+
+// Verifies a check spread for the channels of one frequency, e.g., 'often=0.2'.
+pub(crate) fn parse_frequency_check_spread(arg: &str) -> anyhow::Result<(Frequencies, f64)> {
+    parse_frequency_override(arg, parse_check_spread)
+}
+
+// Verifies a check jitter for the channels of one frequency, e.g., 'rarely=1.0'.
+pub(crate) fn parse_frequency_check_jitter(arg: &str) -> anyhow::Result<(Frequencies, f64)> {
+    parse_frequency_override(arg, parse_check_jitter)
+}
+
+// Return the value of a check parameter for the channels of the supplied
+// frequency: the one configured for the frequency, if any, and the global one
+// otherwise. Later overrides for the same frequency win.
+fn value_for_frequency(freq: &Frequencies, overrides: &[(Frequencies, f64)], global: f64) -> f64 {
+    overrides
+        .iter()
+        .rev()
+        .find_map(|(f, v)| (f == freq).then_some(*v))
+        .unwrap_or(global)
+}
+
+#[derive(sqlx::FromRow)]
 // Type that represents the results returned from the below database. This is
 // needed so that we can specify the input type for function
 // `shuf_channels_gen_sleeps`.
@@ -52,22 +148,35 @@ struct Channel {
 // (in seconds) for which the calling task will sleep for in between sending out
 // check channel messages. This function scopes the `rng` variable, as the
 // `ThreadRng` is not `Send` and can thus not be used across `.await` points.
-fn shuf_channels_gen_sleeps(channels: &mut [Channel], dur_secs: f64) -> anyhow::Result<Vec<u64>> {
+fn shuf_channels_gen_sleeps(
+    channels: &mut [Channel],
+    dur_secs: f64,
+    spread: f64,
+    jitter: f64,
+) -> anyhow::Result<Vec<u64>> {
     let mut rng = rand::rng();
 
     // Shuffle vector of channels returned from the database so that we visit them
     // in different orders each time we check on them.
     channels.shuffle(&mut rng);
 
-    // We'll spread the check channel message emissions across the first half of the
-    // interval. In order to increase how "random" autotube's RSS feed requests
-    // look, however, we'll add some jitter from (-jitter_end, jitter_end) to each
-    // moment in time. Example: 3600 seconds interval with 10 channels to check on
-    // in it => step_secs = 180. Thus, on average, we'll emit a message each 180
-    // seconds, however, shifted by a number of seconds sampled uniformly at random
-    // from (-90.0, 90.0).
-    let step_secs = dur_secs / (2.0 * channels.len() as f64);
-    let jitter_end = step_secs / 2.0;
+    // We'll spread the check channel message emissions across the first `spread`
+    // fraction of the interval (by default, the first half). In order to increase
+    // how "random" autotube's RSS feed requests look, however, we'll add some
+    // jitter from (-jitter_end, jitter_end) to each moment in time, where
+    // jitter_end is the `jitter` proportion of the step (by default, half of it).
+    // Example: 3600 seconds interval with 10 channels to check on in it and the
+    // default settings => step_secs = 180. Thus, on average, we'll emit a message
+    // each 180 seconds, however, shifted by a number of seconds sampled uniformly
+    // at random from (-90.0, 90.0). As `jitter` is at most 1.0, sleeps can't turn
+    // negative.
+    if !(spread > 0.0 && spread <= 1.0 && (0.0..=1.0).contains(&jitter)) {
+        return Err(anyhow::anyhow!(
+            "Spread {spread} and jitter {jitter} don't result in non-negative sleeps"
+        ));
+    }
+    let step_secs = dur_secs * spread / channels.len() as f64;
+    let jitter_end = step_secs * jitter;
     let Ok(range) = rand::distr::Uniform::new_inclusive(-jitter_end, jitter_end) else {
         return Err(anyhow::anyhow!(
             "Failed to construct random distribution over ({}, {})",
@@ -90,18 +199,172 @@ fn shuf_channels_gen_sleeps(channels: &mut [Channel], dur_secs: f64) -> anyhow::
 #[derive(Clone, Debug)]
 /// Wraps state that the time-based job trigger task needs to have access to.
 pub(crate) struct TriggerState {
-    submit_job: tokio::sync::mpsc::Sender<Job>,
-    db_pool: sqlx::sqlite::SqlitePool,
+    submit_job: JobQueue,
+    db_pool: sqlx::AnyPool,
+    check_spread: f64,
+    check_jitter: f64,
+    check_spread_for: Vec<(Frequencies, f64)>,
+    check_jitter_for: Vec<(Frequencies, f64)>,
+    check_on_startup: bool,
+    startup_jitter_secs: u32,
+    active_downloads: ActiveDownloads,
+    throttle_checks_above: Option<u16>,
+    checks_triggered: std::sync::Arc<AtomicU64>,
 }
 
 impl TriggerState {
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn new(
-        submit_job: &tokio::sync::mpsc::Sender<Job>,
-        db_pool: &sqlx::sqlite::SqlitePool,
+        submit_job: &JobQueue,
+        db_pool: &sqlx::AnyPool,
+        check_spread: f64,
+        check_jitter: f64,
+        check_spread_for: Vec<(Frequencies, f64)>,
+        check_jitter_for: Vec<(Frequencies, f64)>,
+        check_on_startup: bool,
+        startup_jitter_secs: u32,
+        active_downloads: &ActiveDownloads,
+        throttle_checks_above: Option<u16>,
     ) -> Self {
         TriggerState {
             submit_job: submit_job.clone(),
             db_pool: db_pool.clone(),
+            check_spread,
+            check_jitter,
+            check_spread_for,
+            check_jitter_for,
+            check_on_startup,
+            startup_jitter_secs,
+            active_downloads: active_downloads.clone(),
+            throttle_checks_above,
+            checks_triggered: std::sync::Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Return a handle to the number of channel checks triggered so far, which
+    /// remains readable after the trigger has shut down.
+    pub(crate) fn checks_triggered(&self) -> std::sync::Arc<AtomicU64> {
+        std::sync::Arc::clone(&self.checks_triggered)
+    }
+
+    // If throttling is configured, wait for as long as more than the configured
+    // number of downloads are in progress, so that checks don't discover further
+    // videos to download while the bandwidth is taken up by the running ones.
+    async fn await_download_capacity(&self, freq: &Frequencies) {
+        let Some(threshold) = self.throttle_checks_above else {
+            return;
+        };
+
+        let mut throttled = false;
+        loop {
+            let in_progress = self
+                .active_downloads
+                .lock()
+                .map_or(0, |active| active.len());
+            if in_progress <= usize::from(threshold) {
+                break;
+            }
+
+            if !throttled {
+                event!(
+                    Level::INFO,
+                    "Delaying checks of '{freq}' trigger while {in_progress} downloads are in progress"
+                );
+                throttled = true;
+            }
+            tokio::time::sleep(tokio::time::Duration::from_secs(THROTTLE_POLL_SECS)).await;
+        }
+
+        if throttled {
+            event!(Level::INFO, "Resuming checks of '{freq}' trigger");
+        }
+    }
+
+    // Return the time the next tick of the trigger for the supplied frequency was
+    // due at when autotube last ran, if it was persisted.
+    async fn persisted_next_tick(
+        &self,
+        freq: &Frequencies,
+    ) -> Option<chrono::DateTime<chrono::Utc>> {
+        match sqlx::query_scalar::<_, String>(&format!(
+            "SELECT next_tick_at FROM {trigger_schedule} WHERE frequency = $1;",
+            trigger_schedule = db::table("trigger_schedule")
+        ))
+        .bind(freq.to_string())
+        .fetch_optional(&self.db_pool)
+        .await
+        {
+            Ok(next_tick_at) => next_tick_at
+                .and_then(|at| chrono::DateTime::parse_from_rfc3339(&at).ok())
+                .map(|at| at.with_timezone(&chrono::Utc)),
+            Err(e) => {
+                event!(
+                    Level::WARN,
+                    "Trigger failed to retrieve schedule of frequency '{freq}': {e}"
+                );
+                None
+            }
+        }
+    }
+
+    // Persist that the next tick of the trigger for the supplied frequency is due
+    // in `due_in`, so that the schedule can be resumed after a restart.
+    async fn persist_next_tick(&self, freq: &Frequencies, due_in: tokio::time::Duration) {
+        let next_tick_at = chrono::TimeDelta::from_std(due_in)
+            .ok()
+            .and_then(|d| chrono::Utc::now().checked_add_signed(d))
+            .unwrap_or_else(chrono::Utc::now);
+        if let Err(e) = sqlx::query(&format!(
+            "INSERT INTO {trigger_schedule} ( frequency, next_tick_at )
+            VALUES ( $1, $2 )
+            ON CONFLICT (frequency) DO UPDATE SET next_tick_at = excluded.next_tick_at;",
+            trigger_schedule = db::table("trigger_schedule")
+        ))
+        .bind(freq.to_string())
+        .bind(next_tick_at.fixed_offset().format("%+").to_string())
+        .execute(&self.db_pool)
+        .await
+        {
+            event!(
+                Level::WARN,
+                "Trigger failed to persist schedule of frequency '{freq}': {e}"
+            );
+        }
+    }
+
+    // Return how long to wait for the first tick of the trigger for the supplied
+    // frequency, whose ticks are `dur` apart. The schedule persisted before autotube
+    // last shut down is resumed if its next tick is still to come, so that restarts
+    // don't reset the check cadence. Otherwise, the first tick is delayed by a
+    // random number of seconds up to the configured maximum, so that the checks of
+    // all frequencies don't start at the same time right after startup. If all
+    // channels are checked right away on startup, a tick missed meanwhile is
+    // covered by that and the first tick is additionally deferred by `dur`.
+    async fn first_tick(
+        &self,
+        freq: &Frequencies,
+        dur: tokio::time::Duration,
+    ) -> tokio::time::Duration {
+        if let Some(remaining) = self
+            .persisted_next_tick(freq)
+            .await
+            .and_then(|at| at.signed_duration_since(chrono::Utc::now()).to_std().ok())
+        {
+            event!(
+                Level::INFO,
+                "Resuming schedule of '{freq}' trigger, next tick due in {} seconds",
+                remaining.as_secs()
+            );
+            return remaining.min(dur);
+        }
+
+        let jitter = tokio::time::Duration::from_secs(rand::random_range(
+            0..=u64::from(self.startup_jitter_secs),
+        ));
+        if self.check_on_startup {
+            jitter.saturating_add(dur)
+        } else {
+            jitter
         }
     }
 
@@ -109,18 +372,38 @@ impl TriggerState {
     // frequency on the worker queue so that a worker task goes out and checks the
     // channel's RSS feed for any new video to download.
     async fn trigger_checks(self, freq: &Frequencies) {
-        event!(Level::INFO, "Setting up trigger for frequency '{freq}'");
+        let check_spread = value_for_frequency(freq, &self.check_spread_for, self.check_spread);
+        let check_jitter = value_for_frequency(freq, &self.check_jitter_for, self.check_jitter);
+        event!(
+            Level::INFO,
+            "Setting up trigger for frequency '{freq}' (spread {check_spread}, jitter {check_jitter})"
+        );
+
+        let dur = tokio::time::Duration::from_mins(freq.get_dur_mins());
+        let first_tick = self.first_tick(freq, dur).await;
+        event!(
+            Level::DEBUG,
+            "First tick of '{freq}' trigger due in {} seconds",
+            first_tick.as_secs()
+        );
+
+        // Persist when the next tick is due, now and after each tick, so that a
+        // restart can resume the schedule.
+        self.persist_next_tick(freq, first_tick).await;
 
         // Prepare the future that will wake up exactly each `get_dur_mins()` minutes,
         // regardless of how long the computations between ticks take.
-        let dur = tokio::time::Duration::from_mins(freq.get_dur_mins());
-        let mut interval = tokio::time::interval(dur);
+        let start = tokio::time::Instant::now()
+            .checked_add(first_tick)
+            .unwrap_or_else(tokio::time::Instant::now);
+        let mut interval = tokio::time::interval_at(start, dur);
         let dur_secs = dur.as_secs_f64();
 
         loop {
             // Wait until the next tick has occurred.
             let _ = interval.tick().await;
             event!(Level::DEBUG, "Next tick for '{freq}' trigger occurred");
+            self.persist_next_tick(freq, dur).await;
 
             // Retrieve all RSS feed URLs of channels marked to be checked with this
             // particular frequency from the database. Note how we exclude channels which
@@ -128,15 +411,16 @@ impl TriggerState {
             // the job of the follow channel job to conduct the initial check (including
             // potential download of videos) and initialize the `last_checked` field to its
             // first actual timestamp. This way, we prevent concurrent access issues between
-            // trigger and worker tasks.
+            // trigger and worker tasks. Unfollowed channels (`deleted_at` != NULL) are
+            // excluded as well.
             let freq_str = freq.to_string();
-            let mut channels = match sqlx::query_as!(
-                Channel,
+            let mut channels = match sqlx::query_as::<_, Channel>(&format!(
                 "SELECT feed_url
-                FROM channels
-                WHERE check_frequency = $1 AND last_checked IS NOT NULL;",
-                freq_str,
-            )
+                FROM {channels}
+                WHERE check_frequency = $1 AND last_checked IS NOT NULL AND deleted_at IS NULL;",
+                channels = db::table("channels")
+            ))
+            .bind(&freq_str)
             .fetch_all(&self.db_pool)
             .await
             {
@@ -160,19 +444,22 @@ impl TriggerState {
 
             // Obtain the generated vector of durations to sleep between check channel
             // message emissions and also shuffle the `channels` vector.
-            let sleeps = match shuf_channels_gen_sleeps(&mut channels, dur_secs) {
-                Ok(j) => j,
-                Err(e) => {
-                    event!(Level::WARN, "Trigger failed on rand operations: {e}");
-                    return;
-                }
-            };
+            let sleeps =
+                match shuf_channels_gen_sleeps(&mut channels, dur_secs, check_spread, check_jitter)
+                {
+                    Ok(j) => j,
+                    Err(e) => {
+                        event!(Level::WARN, "Trigger failed on rand operations: {e}");
+                        return;
+                    }
+                };
 
             // As we only want to sleep between message emissions (and not after having sent
             // the final message for this iterator of channels), we make use of the peekable
             // version of the channels iterator, to be able to look ahead.
             let mut channels_sleeps = channels.into_iter().zip(sleeps).peekable();
             while let Some((channel, sleep)) = channels_sleeps.next() {
+                self.await_download_capacity(freq).await;
                 if self
                     .submit_job
                     .send(Job::Check(JobCheckChannel::new(channel.feed_url)))
@@ -185,6 +472,7 @@ impl TriggerState {
                     );
                     return;
                 }
+                self.checks_triggered.fetch_add(1, Ordering::Relaxed);
 
                 // If there's still at least one channel to come for this iterator, sleep.
                 if channels_sleeps.peek().is_some() {
@@ -194,13 +482,142 @@ impl TriggerState {
         }
     }
 
+    // Place a check channel message for every followed channel on the worker queue
+    // right away, catching up on videos published while autotube wasn't running.
+    // Channels that haven't been checked at all thus far are excluded for the same
+    // reason as in `trigger_checks`. The number of RSS feeds fetched concurrently
+    // remains limited as configured.
+    async fn check_all_channels(self) {
+        let channels = match sqlx::query_as::<_, Channel>(&format!(
+            "SELECT feed_url
+            FROM {channels}
+            WHERE last_checked IS NOT NULL AND deleted_at IS NULL;",
+            channels = db::table("channels")
+        ))
+        .fetch_all(&self.db_pool)
+        .await
+        {
+            Ok(c) => c,
+            Err(e) => {
+                event!(
+                    Level::WARN,
+                    "Trigger failed to retrieve channels to check on startup: {e}",
+                );
+                return;
+            }
+        };
+
+        event!(
+            Level::INFO,
+            "Checking all {} followed channels on startup",
+            channels.len()
+        );
+        for channel in channels {
+            if self
+                .submit_job
+                .send(Job::Check(JobCheckChannel::new(channel.feed_url)))
+                .await
+                .is_err()
+            {
+                event!(
+                    Level::WARN,
+                    "Submit channel to worker queue errored, aborting",
+                );
+                return;
+            }
+            self.checks_triggered.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
     pub(crate) async fn run(self, mut recv_shutdown: tokio::sync::broadcast::Receiver<()>) {
         let mut set = tokio::task::JoinSet::new();
         for freq in &Frequencies::VARIANTS {
             set.spawn(self.clone().trigger_checks(freq));
         }
+        if self.check_on_startup {
+            set.spawn(self.clone().check_all_channels());
+        }
         let _ = recv_shutdown.recv().await;
         event!(Level::DEBUG, "Trigger shutting down...");
         let () = set.shutdown().await;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shuf_channels_gen_sleeps() {
+        let mut channels = (0..10)
+            .map(|i| Channel {
+                feed_url: format!("https://www.youtube.com/feeds/videos.xml?channel_id={i}"),
+            })
+            .collect::<Vec<Channel>>();
+
+        // With the default settings, checks are spread across the first half of the
+        // interval, each shifted by at most half a step.
+        let Ok(sleeps) = shuf_channels_gen_sleeps(&mut channels, 3600.0, 0.5, 0.5) else {
+            panic!("Failed to generate sleeps for default settings");
+        };
+        assert_eq!(sleeps.len(), 10);
+        assert!(sleeps.iter().all(|s| (90..=270).contains(s)));
+
+        // Spreading across the full interval with maximum jitter must not produce
+        // sleeps beyond twice the step.
+        let Ok(sleeps) = shuf_channels_gen_sleeps(&mut channels, 3600.0, 1.0, 1.0) else {
+            panic!("Failed to generate sleeps for full interval");
+        };
+        assert!(sleeps.iter().all(|s| *s <= 720));
+
+        assert!(shuf_channels_gen_sleeps(&mut channels, 3600.0, 0.0, 0.5).is_err());
+        assert!(shuf_channels_gen_sleeps(&mut channels, 3600.0, 0.5, 1.5).is_err());
+
+        assert!(parse_check_spread("1.0").is_ok_and(|s| (s - 1.0).abs() < f64::EPSILON));
+        assert!(parse_check_spread("0").is_err());
+        assert!(parse_check_spread("1.1").is_err());
+        assert!(parse_check_jitter("0").is_ok());
+        assert!(parse_check_jitter("-0.1").is_err());
+        assert!(parse_check_jitter("abc").is_err());
+    }
+
+    #[test]
+    fn test_frequency_check_patterns() {
+        assert_eq!(
+            parse_frequency_check_spread("often=0.2").ok(),
+            Some((Frequencies::Often, 0.2))
+        );
+        assert_eq!(
+            parse_frequency_check_jitter(" rarely = 1.0").ok(),
+            Some((Frequencies::Rarely, 1.0))
+        );
+        assert!(parse_frequency_check_spread("often").is_err());
+        assert!(parse_frequency_check_spread("hourly=0.5").is_err());
+        assert!(parse_frequency_check_spread("often=0").is_err());
+        assert!(parse_frequency_check_jitter("rarely=-0.5").is_err());
+
+        // Frequencies without overrides fall back to the global settings, and later
+        // overrides of the same frequency win.
+        let overrides = [(Frequencies::Often, 0.2), (Frequencies::Often, 0.1)];
+        assert!(
+            (value_for_frequency(&Frequencies::Often, &overrides, 0.5) - 0.1).abs() < f64::EPSILON
+        );
+        assert!(
+            (value_for_frequency(&Frequencies::Rarely, &overrides, 0.5) - 0.5).abs() < f64::EPSILON
+        );
+    }
+
+    #[test]
+    fn test_estimate_next_check() {
+        assert_eq!(
+            estimate_next_check("often", "2026-01-01T10:00:00+00:00"),
+            chrono::DateTime::parse_from_rfc3339("2026-01-01T12:00:00+00:00").ok()
+        );
+        assert_eq!(
+            estimate_next_check("rarely", "2026-01-01T10:00:00+01:00"),
+            chrono::DateTime::parse_from_rfc3339("2026-01-02T10:00:00+01:00").ok()
+        );
+        assert!(estimate_next_check("hourly", "2026-01-01T10:00:00+00:00").is_none());
+        assert!(estimate_next_check("sometimes", "yesterday").is_none());
+    }
+}